@@ -1,12 +1,23 @@
+use crate::detached::{self, ExecRequest};
+
 use chrono::{DateTime, Local};
-use serde::Deserialize;
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use std::{
+    future::Future,
+    path::{Component, Path},
+};
 
 mod counting_reader;
 mod tar;
 mod zip;
 
-#[derive(Debug, Deserialize)]
+/// What vanilla (and everything based on it) prints once `save-all` has written the world, and
+/// what older versions printed instead.
+const SAVE_COMPLETE: &[&str] = &["Saved the game", "Save complete"];
+/// How long the server gets to write the world for `save-all flush`.
+const SAVE_TIMEOUT_MS: u64 = 300_000;
+
+#[derive(Debug, Serialize, Deserialize)]
 pub enum BackupFormat {
     #[serde(rename = "zip")]
     Zip,
@@ -100,6 +111,90 @@ pub fn create(name: &str, format: &BackupFormat) -> Result<(), anyhow::Error> {
     }
 }
 
+fn console_command(command: &str, until: &[&str], timeout_ms: u64) -> ExecRequest {
+    ExecRequest {
+        command: command.to_string(),
+        quiet_ms: 500,
+        timeout_ms,
+        identity: None,
+        until: until.iter().map(|until| until.to_string()).collect(),
+    }
+}
+
+/// Creates a backup of a running server. Autosaving is turned off and the world is flushed to
+/// disk first, so no region file changes while it is archived, and turned back on afterwards
+/// even if the backup failed. `exec` runs a console command and returns its output.
+pub async fn create_live<F, Fut>(
+    name: &str,
+    format: BackupFormat,
+    exec: F,
+) -> Result<(), anyhow::Error>
+where
+    F: Fn(ExecRequest) -> Fut,
+    Fut: Future<Output = Result<Vec<u8>, anyhow::Error>>,
+{
+    exec(console_command("save-off", &[], 5000)).await?;
+
+    let result = async {
+        let output = exec(console_command(
+            "save-all flush",
+            SAVE_COMPLETE,
+            SAVE_TIMEOUT_MS,
+        ))
+        .await?;
+        let output = String::from_utf8_lossy(&output);
+        if !SAVE_COMPLETE.iter().any(|line| output.contains(line)) {
+            return Err(anyhow::anyhow!(
+                "the server did not confirm saving the world"
+            ));
+        }
+
+        let name = name.to_string();
+        tokio::task::spawn_blocking(move || create(&name, &format)).await?
+    }
+    .await;
+
+    let save_on = exec(console_command("save-on", &[], 5000)).await;
+
+    result.and(save_on.map(|_| ()))
+}
+
+/// Whether a path in the server directory is one of mcvcli's own, which are left out of backups.
+/// Only the first component counts, so e.g. `world/.mcvcli.backups` is still backed up.
+fn excluded(path: &Path) -> bool {
+    path.components()
+        .find(|component| matches!(component, Component::Normal(_)))
+        .is_some_and(|component| {
+            matches!(
+                component.as_os_str().to_str(),
+                Some(".mcvcli.backups" | ".mcvcli.profiles" | detached::DIR)
+            )
+        })
+}
+
+/// Removes everything in the server directory except mcvcli's own files, before a restore.
+pub fn wipe() -> Result<(), anyhow::Error> {
+    for entry in std::fs::read_dir(".")?.flatten() {
+        let path = entry.path();
+
+        if path
+            .file_name()
+            .map(|name| name.to_string_lossy().starts_with(".mcvcli"))
+            .unwrap_or(false)
+        {
+            continue;
+        }
+
+        if path.is_dir() {
+            std::fs::remove_dir_all(&path)?;
+        } else {
+            std::fs::remove_file(&path)?;
+        }
+    }
+
+    Ok(())
+}
+
 pub fn restore(backup: &Backup) -> Result<(), anyhow::Error> {
     let path = &backup.path;
 
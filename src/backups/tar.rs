@@ -14,10 +14,7 @@ fn recursive_add_directory(
     for entry in std::fs::read_dir(directory)?.flatten() {
         let path = entry.path();
 
-        let Some(file_name) = path.file_name() else {
-            continue;
-        };
-        if file_name == ".mcvcli.backups" || file_name == ".mcvcli.profiles" {
+        if super::excluded(&path) {
             continue;
         }
 
@@ -77,9 +74,7 @@ pub fn create(name: &str, encoder: TarEncoder, extension: &str) -> Result<(), an
 
     let mut total_size = 0;
     for entry in walkdir::WalkDir::new(".").into_iter().flatten() {
-        let path = entry.path().to_string_lossy();
-
-        if path.contains(".mcvcli.backups") || path.contains(".mcvcli.profiles") {
+        if super::excluded(entry.path()) {
             continue;
         }
 
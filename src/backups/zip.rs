@@ -13,16 +13,18 @@ fn recursive_add_directory(
     for entry in std::fs::read_dir(directory)?.flatten() {
         let path = entry.path();
 
-        let Some(file_name) = path.file_name() else {
-            continue;
-        };
-        if file_name == ".mcvcli.backups" || file_name == ".mcvcli.profiles" {
+        if super::excluded(&path) {
             continue;
         }
 
-        if path.is_dir() {
+        let metadata = match path.metadata() {
+            Ok(metadata) => metadata,
+            Err(_) => continue,
+        };
+
+        if metadata.is_dir() {
             #[cfg(unix)]
-            if let Ok(metadata) = path.metadata() {
+            {
                 use std::os::unix::fs::PermissionsExt;
 
                 options = options.unix_permissions(metadata.permissions().mode());
@@ -30,9 +32,9 @@ fn recursive_add_directory(
 
             zip.add_directory(path.strip_prefix(root)?.to_string_lossy(), options)?;
             recursive_add_directory(zip, &path, root, options, progress)?;
-        } else {
+        } else if metadata.is_file() {
             #[cfg(unix)]
-            if let Ok(metadata) = path.metadata() {
+            {
                 use std::os::unix::fs::PermissionsExt;
 
                 options = options
@@ -71,9 +73,7 @@ pub fn create(name: &str) -> Result<(), anyhow::Error> {
 
     let mut total_size = 0;
     for entry in walkdir::WalkDir::new(".").into_iter().flatten() {
-        let path = entry.path().to_string_lossy();
-
-        if path.contains(".mcvcli.backups") || path.contains(".mcvcli.profiles") {
+        if super::excluded(entry.path()) {
             continue;
         }
 
@@ -0,0 +1,186 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+const ROOT: &str = "/sys/fs/cgroup";
+const CPU_PERIOD: u64 = 100_000;
+const CONTROLLERS: &str = "+memory +cpu +pids";
+
+/// Resource limits for the server process, applied through a cgroup v2 sub-group on Linux.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Limits {
+    pub memory_max_mb: Option<u64>,
+    pub cpu_max: Option<f64>,
+    pub pids_max: Option<u64>,
+}
+
+impl Limits {
+    pub fn is_empty(&self) -> bool {
+        self.memory_max_mb.is_none() && self.cpu_max.is_none() && self.pids_max.is_none()
+    }
+}
+
+#[derive(Debug)]
+pub struct Usage {
+    pub memory_current: u64,
+    pub memory_max: Option<u64>,
+    pub cpu_usage_usec: u64,
+    pub cpu_max: Option<f64>,
+    pub pids_current: u64,
+    pub pids_max: Option<u64>,
+}
+
+/// The cgroup a process belongs to, from the unified (`0::`) line of `/proc/<pid>/cgroup`.
+fn cgroup_of(pid: &str) -> Option<PathBuf> {
+    let content = std::fs::read_to_string(format!("/proc/{pid}/cgroup")).ok()?;
+    let path = content.lines().find_map(|line| line.strip_prefix("0::"))?;
+
+    Some(Path::new(ROOT).join(path.trim_start_matches('/')))
+}
+
+fn write(path: &Path, value: &str) -> Result<(), anyhow::Error> {
+    std::fs::write(path, value)
+        .map_err(|err| anyhow::anyhow!("failed to write {}: {err}", path.display()))
+}
+
+fn read_value(path: &Path) -> Option<u64> {
+    std::fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// The cgroup the daemon was started in, which [`setup`] creates the server's group in. Has to be
+/// looked up before the first `setup`, which may move the daemon into a leaf below it.
+pub fn parent() -> Option<PathBuf> {
+    cgroup_of("self")
+}
+
+fn daemon_leaf(parent: &Path, identifier: &str) -> PathBuf {
+    parent.join(format!("mcvcli-{identifier}-daemon"))
+}
+
+/// Creates (or updates) the cgroup for a daemon's server below `parent` and writes its limits.
+/// Controllers can only be enabled for a cgroup without processes of its own, so if the daemon
+/// shares its cgroup it first moves itself into a leaf next to the server's.
+pub fn setup(parent: &Path, identifier: &str, limits: &Limits) -> Result<PathBuf, anyhow::Error> {
+    if !Path::new(ROOT).join("cgroup.controllers").exists() {
+        return Err(anyhow::anyhow!("cgroup v2 is not mounted at {ROOT}"));
+    }
+
+    if write(&parent.join("cgroup.subtree_control"), CONTROLLERS).is_err() {
+        let leaf = daemon_leaf(parent, identifier);
+        std::fs::create_dir_all(&leaf)?;
+        write(&leaf.join("cgroup.procs"), &std::process::id().to_string())?;
+
+        write(&parent.join("cgroup.subtree_control"), CONTROLLERS)?;
+    }
+
+    let group = parent.join(format!("mcvcli-{identifier}"));
+    std::fs::create_dir_all(&group)?;
+
+    write(
+        &group.join("memory.max"),
+        &limits
+            .memory_max_mb
+            .map_or("max".to_string(), |mb| (mb * 1024 * 1024).to_string()),
+    )?;
+    write(
+        &group.join("cpu.max"),
+        &format!(
+            "{} {CPU_PERIOD}",
+            limits.cpu_max.map_or("max".to_string(), |cores| {
+                ((cores * CPU_PERIOD as f64) as u64).max(1000).to_string()
+            })
+        ),
+    )?;
+    write(
+        &group.join("pids.max"),
+        &limits
+            .pids_max
+            .map_or("max".to_string(), |pids| pids.to_string()),
+    )?;
+
+    Ok(group)
+}
+
+/// The `cgroup.procs` file of a group, opened for a child process to move itself in before it
+/// execs, see `spawn_java` in the daemon.
+pub fn procs(group: &Path) -> Result<File, anyhow::Error> {
+    let path = group.join("cgroup.procs");
+
+    std::fs::OpenOptions::new()
+        .write(true)
+        .open(&path)
+        .map_err(|err| anyhow::anyhow!("failed to open {}: {err}", path.display()))
+}
+
+/// Removes a cgroup once its processes are gone.
+pub fn remove(group: &Path) {
+    let _ = std::fs::remove_dir(group);
+}
+
+/// Moves the daemon back out of the leaf [`setup`] put it in and removes the leaf, once the
+/// server's group is gone. The controllers have to be disabled in the parent for that, so the
+/// leaf is left alone while other groups below the parent may still use them.
+pub fn release(parent: &Path, identifier: &str) {
+    let leaf = daemon_leaf(parent, identifier);
+    if !leaf.exists() {
+        return;
+    }
+
+    let shared = std::fs::read_dir(parent).map_or(true, |entries| {
+        entries
+            .flatten()
+            .any(|entry| entry.file_type().is_ok_and(|kind| kind.is_dir()) && entry.path() != leaf)
+    });
+    if shared {
+        return;
+    }
+
+    if write(
+        &parent.join("cgroup.subtree_control"),
+        &CONTROLLERS.replace('+', "-"),
+    )
+    .is_ok()
+        && write(
+            &parent.join("cgroup.procs"),
+            &std::process::id().to_string(),
+        )
+        .is_ok()
+    {
+        remove(&leaf);
+    }
+}
+
+/// The cgroup created by [`setup`] that a process was placed in, if any.
+pub fn of_process(pid: u32) -> Option<PathBuf> {
+    cgroup_of(&pid.to_string()).filter(|path| {
+        path.file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with("mcvcli-"))
+    })
+}
+
+pub fn usage(group: &Path) -> Option<Usage> {
+    let cpu_usage_usec = std::fs::read_to_string(group.join("cpu.stat"))
+        .ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("usage_usec "))
+        .and_then(|usage| usage.trim().parse().ok())
+        .unwrap_or(0);
+
+    let cpu_max = std::fs::read_to_string(group.join("cpu.max"))
+        .ok()
+        .and_then(|max| {
+            let (quota, period) = max.trim().split_once(' ')?;
+            Some(quota.parse::<f64>().ok()? / period.parse::<f64>().ok()?)
+        });
+
+    Some(Usage {
+        memory_current: read_value(&group.join("memory.current"))?,
+        memory_max: read_value(&group.join("memory.max")),
+        cpu_usage_usec,
+        cpu_max,
+        pids_current: read_value(&group.join("pids.current")).unwrap_or(0),
+        pids_max: read_value(&group.join("pids.max")),
+    })
+}
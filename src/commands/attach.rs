@@ -1,11 +1,28 @@
-use crate::detached;
+use crate::{
+    commands::crash::format_summary,
+    console::{Console, Input},
+    crash, detached,
+    logs::Event,
+    remotes,
+};
 
 use clap::ArgMatches;
 use colored::Colorize;
+use std::sync::Arc;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
-pub async fn attach(_matches: &ArgMatches) -> Result<i32, anyhow::Error> {
-    if !detached::is_running() {
+pub async fn attach(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
+    let read_only = *matches.get_one::<bool>("read_only").expect("required");
+    let label = matches.get_one::<String>("label").cloned();
+    let remote = match remotes::resolve(matches) {
+        Ok(remote) => remote,
+        Err(err) => {
+            println!("{}", err.to_string().red());
+            return Ok(1);
+        }
+    };
+
+    if remote.is_none() && !detached::is_running() {
         println!(
             "{} {}",
             "server is not running, use".red(),
@@ -16,46 +33,105 @@ pub async fn attach(_matches: &ArgMatches) -> Result<i32, anyhow::Error> {
 
     println!("{}", "attaching to server ...".bright_black());
 
-    let connection = match detached::connect().await {
+    let connection = match detached::connect_to(remote.as_ref()).await {
         Ok(connection) => connection,
+        Err(err) if remote.is_some() => {
+            println!("{} {}", "failed to connect to remote:".red(), err);
+            return Ok(1);
+        }
         Err(_) => {
             println!(
                 "{} {}",
                 "the server is orphaned (its supervisor is gone), use".red(),
-                "mcvcli stop".cyan()
+                "mcvcli recover".cyan()
             );
             return Ok(1);
         }
     };
     let (mut reader, mut writer) = tokio::io::split(connection);
 
-    detached::write_frame(&mut writer, detached::TAG_ATTACH, &[]).await?;
+    detached::write_frame(
+        &mut writer,
+        detached::TAG_ATTACH,
+        &serde_json::to_vec(&detached::Identity::current(label, read_only))?,
+    )
+    .await?;
 
     println!(
         "{} {}",
         "attaching to server ...".bright_black(),
         "DONE".green().bold()
     );
-    println!(
-        "{}",
-        "(press ctrl-c to detach without stopping)".bright_black()
-    );
+    if read_only {
+        println!(
+            "{}",
+            "(read-only, press ctrl-c to detach without stopping)".bright_black()
+        );
+    } else {
+        println!(
+            "{}",
+            "(press ctrl-c to detach without stopping)".bright_black()
+        );
+    }
     println!();
 
+    // With a terminal, lines are read through the console editor, otherwise stdin is forwarded
+    // as is so piping commands into attach keeps working.
+    let (console, input) = match (!read_only)
+        .then(|| Console::start(remote.is_none().then(detached::history_path)))
+        .flatten()
+    {
+        Some((console, input)) => {
+            if let Ok(status) = detached::status(remote.as_ref()).await {
+                console.set_players(status.players);
+            }
+
+            (Some(console), Some(input))
+        }
+        None => (None, None),
+    };
+    let detach = Arc::new(tokio::sync::Notify::new());
+
     // Forward terminal stdin to the server.
-    let stdin_task = tokio::spawn(async move {
-        let mut stdin = tokio::io::stdin();
-        let mut buffer = [0u8; 1024];
+    let stdin_task = tokio::spawn({
+        let detach = Arc::clone(&detach);
 
-        loop {
-            match stdin.read(&mut buffer).await {
-                Ok(0) | Err(_) => break,
-                Ok(n) => {
-                    if detached::write_frame(&mut writer, detached::TAG_STDIN, &buffer[..n])
-                        .await
-                        .is_err()
+        async move {
+            if read_only {
+                return;
+            }
+
+            if let Some(mut input) = input {
+                while let Some(Input::Line(line)) = input.recv().await {
+                    if detached::write_frame(
+                        &mut writer,
+                        detached::TAG_STDIN,
+                        format!("{line}\n").as_bytes(),
+                    )
+                    .await
+                    .is_err()
                     {
-                        break;
+                        return;
+                    }
+                }
+
+                detach.notify_one();
+                return;
+            }
+
+            let mut stdin = tokio::io::stdin();
+            let mut buffer = [0u8; 1024];
+
+            loop {
+                match stdin.read(&mut buffer).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => {
+                        if detached::write_frame(&mut writer, detached::TAG_STDIN, &buffer[..n])
+                            .await
+                            .is_err()
+                        {
+                            break;
+                        }
                     }
                 }
             }
@@ -68,6 +144,11 @@ pub async fn attach(_matches: &ArgMatches) -> Result<i32, anyhow::Error> {
 
         loop {
             match detached::read_frame(&mut reader).await {
+                Ok((detached::TAG_STDOUT | detached::TAG_STDERR, data))
+                    if let Some(console) = &console =>
+                {
+                    console.print(&data);
+                }
                 Ok((detached::TAG_STDOUT, data)) => {
                     let _ = stdout.write_all(&data).await;
                     let _ = stdout.flush().await;
@@ -76,6 +157,46 @@ pub async fn attach(_matches: &ArgMatches) -> Result<i32, anyhow::Error> {
                     let _ = stderr.write_all(&data).await;
                     let _ = stderr.flush().await;
                 }
+                Ok((detached::TAG_EVENT, data)) => {
+                    if let Some(console) = &console
+                        && let Ok(event) = serde_json::from_slice::<Event>(&data)
+                    {
+                        console.event(&event);
+                    }
+                }
+                Ok((detached::TAG_CRASH, data)) => {
+                    if let Ok(summary) = serde_json::from_slice::<crash::Summary>(&data) {
+                        let message =
+                            format!("{}\n{}", "server crashed".red(), format_summary(&summary));
+
+                        match &console {
+                            Some(console) => console.notice(format!("\n{message}\n")),
+                            None => println!("\n{message}"),
+                        }
+                    }
+                }
+                Ok((detached::TAG_RESTART, data)) => {
+                    if let Ok(notice) = serde_json::from_slice::<detached::RestartNotice>(&data) {
+                        let message = if notice.requested {
+                            "server is restarting ...".bright_black().to_string()
+                        } else {
+                            format!(
+                                "{} {}{} {}{}",
+                                "server exited with code".red(),
+                                notice.code,
+                                ", restarting in".bright_black(),
+                                format!("{}s", notice.delay_secs).cyan(),
+                                format!(" (attempt {}/{}) ...", notice.attempt, notice.max_retries)
+                                    .bright_black()
+                            )
+                        };
+
+                        match &console {
+                            Some(console) => console.notice(format!("\n{message}\n")),
+                            None => println!("\n{message}\n"),
+                        }
+                    }
+                }
                 Ok((detached::TAG_EXIT, data)) => {
                     let code = data
                         .as_slice()
@@ -92,8 +213,14 @@ pub async fn attach(_matches: &ArgMatches) -> Result<i32, anyhow::Error> {
 
     let result = tokio::select! {
         code = stream_future => code,
-        _ = tokio::signal::ctrl_c() => {
+        _ = async {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = detach.notified() => {}
+            }
+        } => {
             stdin_task.abort();
+            drop(console);
             println!();
             println!("{}", "detached (server is still running)".bright_black());
             return Ok(0);
@@ -101,6 +228,7 @@ pub async fn attach(_matches: &ArgMatches) -> Result<i32, anyhow::Error> {
     };
 
     stdin_task.abort();
+    drop(console);
 
     match result {
         Some(code) => {
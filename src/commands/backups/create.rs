@@ -1,4 +1,10 @@
-use crate::{backups, config};
+use crate::{
+    backups,
+    commands::{start::run_hook, webhook},
+    config, detached,
+    hooks::{Environment, Hook},
+    webhooks::{Event, Notification},
+};
 
 use clap::ArgMatches;
 use colored::Colorize;
@@ -8,7 +14,7 @@ pub async fn create(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
     let name = matches.get_one::<String>("name").expect("required");
     let format = matches.get_one::<String>("format").expect("required");
     let format: Option<backups::BackupFormat> = serde_json::from_str(&format!("\"{format}\"")).ok();
-    let _config = config::Config::new(".mcvcli.json", false);
+    let config = config::Config::new(".mcvcli.json", false);
 
     let Some(format) = format else {
         println!(
@@ -38,15 +44,61 @@ pub async fn create(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
         return Ok(1);
     }
 
-    println!("{}", "creating backup...".bright_black());
+    let extension = backups::extension(&format);
 
-    backups::create(name, &format)?;
+    let environment = Environment {
+        backup: Some(name.clone()),
+        ..Environment::new(&config)
+    };
+    if !run_hook(Hook::PreBackup, &config.hooks, &environment, None).await {
+        return Ok(1);
+    }
+
+    if detached::is_running() {
+        println!(
+            "{}",
+            "creating backup (saving is paused while the server is running)...".bright_black()
+        );
+
+        let result = backups::create_live(name, format, |mut request| async move {
+            request.identity = Some(detached::Identity::current(
+                Some("backup".to_string()),
+                false,
+            ));
+            detached::exec(&request).await
+        })
+        .await;
+        if let Err(err) = result {
+            println!("{} {}", "failed to create backup:".red(), err);
+            return Ok(1);
+        }
+
+        println!(
+            "{} {}",
+            "creating backup (saving is paused while the server is running)...".bright_black(),
+            "DONE".green().bold()
+        );
+    } else {
+        println!("{}", "creating backup...".bright_black());
+
+        backups::create(name, &format)?;
+
+        println!(
+            "{} {}",
+            "creating backup...".bright_black(),
+            "DONE".green().bold()
+        );
+    }
+
+    run_hook(Hook::PostBackup, &config.hooks, &environment, None).await;
 
-    println!(
-        "{} {}",
-        "creating backup...".bright_black(),
-        "DONE".green().bold()
-    );
+    webhook::notify(
+        &config,
+        Notification::new(Event::Backup, format!("backup {name} created"))
+            .field("name", name)
+            .field("format", extension),
+    )
+    .await;
 
     Ok(0)
 }
@@ -1,4 +1,10 @@
-use crate::{backups, config, detached};
+use crate::{
+    backups,
+    commands::{start::run_hook, webhook},
+    config, detached,
+    hooks::{Environment, Hook},
+    webhooks::{Event, Notification},
+};
 
 use clap::ArgMatches;
 use colored::Colorize;
@@ -6,7 +12,7 @@ use dialoguer::{Confirm, FuzzySelect, theme::ColorfulTheme};
 
 pub async fn restore(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
     let name = matches.get_one::<String>("name");
-    let _config = config::Config::new(".mcvcli.json", false);
+    let config = config::Config::new(".mcvcli.json", false);
 
     if detached::is_running() {
         println!(
@@ -56,25 +62,17 @@ pub async fn restore(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
         return Ok(1);
     }
 
-    println!("{}", "wiping server directory...".bright_black());
-
-    for entry in std::fs::read_dir(".")?.flatten() {
-        let path = entry.path();
+    let environment = Environment {
+        backup: Some(name.clone()),
+        ..Environment::new(&config)
+    };
+    if !run_hook(Hook::PreRestore, &config.hooks, &environment, None).await {
+        return Ok(1);
+    }
 
-        if path
-            .file_name()
-            .map(|name| name.to_string_lossy().starts_with(".mcvcli"))
-            .unwrap_or(false)
-        {
-            continue;
-        }
+    println!("{}", "wiping server directory...".bright_black());
 
-        if path.is_dir() {
-            std::fs::remove_dir_all(&path)?;
-        } else {
-            std::fs::remove_file(&path)?;
-        }
-    }
+    backups::wipe()?;
 
     println!(
         "{} {}",
@@ -103,5 +101,13 @@ pub async fn restore(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
         "DONE".green().bold()
     );
 
+    run_hook(Hook::PostRestore, &config.hooks, &environment, None).await;
+
+    webhook::notify(
+        &config,
+        Notification::new(Event::Restore, format!("backup {name} restored")).field("name", name),
+    )
+    .await;
+
     Ok(0)
 }
@@ -1,7 +1,8 @@
-use crate::{config, profiles};
+use crate::{config, profiles, tls::Tls};
 
 use clap::ArgMatches;
 use colored::Colorize;
+use rand::{RngExt, distr::Alphanumeric};
 
 pub async fn config(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
     let profile = matches.get_one::<String>("profile");
@@ -28,8 +29,42 @@ pub async fn config(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
     let stop_command = matches.get_one::<String>("stop_command");
     let flags = matches.get_one::<String>("flags");
     let args = matches.get_one::<String>("args");
+    let restart_policy = matches.get_one::<String>("restart_policy");
+    let restart_max_retries = matches.get_one::<u32>("restart_max_retries");
+    let restart_backoff = matches.get_one::<u64>("restart_backoff");
+    let watchdog_interval = matches.get_one::<u64>("watchdog_interval");
+    let watchdog_failures = matches.get_one::<u32>("watchdog_failures");
+    let watchdog_grace = matches.get_one::<u64>("watchdog_grace");
+    let memory_limit = matches.get_one::<u64>("memory_limit");
+    let cpu_limit = matches.get_one::<f64>("cpu_limit");
+    let pids_limit = matches.get_one::<u64>("pids_limit");
+    let metrics_address = matches.get_one::<String>("metrics_address");
+    let remote_address = matches.get_one::<String>("remote_address");
+    let remote_token = matches.get_one::<String>("remote_token");
+    let remote_tls_cert = matches.get_one::<String>("remote_tls_cert");
+    let remote_tls_key = matches.get_one::<String>("remote_tls_key");
+    let remote_tls_ca = matches.get_one::<String>("remote_tls_ca");
 
-    if ram.is_none() && stop_command.is_none() && flags.is_none() && args.is_none() {
+    if ram.is_none()
+        && stop_command.is_none()
+        && flags.is_none()
+        && args.is_none()
+        && restart_policy.is_none()
+        && restart_max_retries.is_none()
+        && restart_backoff.is_none()
+        && watchdog_interval.is_none()
+        && watchdog_failures.is_none()
+        && watchdog_grace.is_none()
+        && memory_limit.is_none()
+        && cpu_limit.is_none()
+        && pids_limit.is_none()
+        && metrics_address.is_none()
+        && remote_address.is_none()
+        && remote_token.is_none()
+        && remote_tls_cert.is_none()
+        && remote_tls_key.is_none()
+        && remote_tls_ca.is_none()
+    {
         println!(
             "{} {}",
             "no changes made, use".bright_black(),
@@ -38,6 +73,40 @@ pub async fn config(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
         return Ok(1);
     }
 
+    // TLS needs all three files, so an empty certificate turns it off and the rest fill in the
+    // current settings.
+    let remote_tls = if remote_tls_cert.is_some_and(|cert| cert.is_empty()) {
+        None
+    } else {
+        let current = config.remote_tls.as_ref();
+        let certificate = remote_tls_cert
+            .cloned()
+            .or_else(|| current.map(|tls| tls.certificate.clone()));
+        let key = remote_tls_key
+            .cloned()
+            .or_else(|| current.map(|tls| tls.key.clone()));
+        let ca = remote_tls_ca
+            .cloned()
+            .or_else(|| current.map(|tls| tls.ca.clone()));
+
+        match (certificate, key, ca) {
+            (Some(certificate), Some(key), Some(ca)) => Some(Tls {
+                certificate,
+                key,
+                ca,
+            }),
+            (None, None, None) => None,
+            _ => {
+                println!(
+                    "{} {}",
+                    "remote TLS needs".red(),
+                    "--remote-tls-cert, --remote-tls-key and --remote-tls-ca".cyan()
+                );
+                return Ok(1);
+            }
+        }
+    };
+
     println!("{}", "updating config ...".bright_black());
 
     if let Some(ram) = ram {
@@ -53,6 +122,64 @@ pub async fn config(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
         config.extra_args = vec![args.to_string()];
     }
 
+    if let Some(restart_policy) = restart_policy {
+        config.restart_policy = serde_json::from_str(&format!("\"{restart_policy}\""))?;
+    }
+    if let Some(restart_max_retries) = restart_max_retries {
+        config.restart_max_retries = *restart_max_retries;
+    }
+    if let Some(restart_backoff) = restart_backoff {
+        config.restart_backoff_secs = *restart_backoff;
+    }
+
+    // An interval of 0 disables the watchdog.
+    if let Some(watchdog_interval) = watchdog_interval {
+        config.watchdog_interval_secs = Some(*watchdog_interval).filter(|interval| *interval > 0);
+    }
+    if let Some(watchdog_failures) = watchdog_failures {
+        config.watchdog_failures = *watchdog_failures;
+    }
+    if let Some(watchdog_grace) = watchdog_grace {
+        config.watchdog_grace_secs = *watchdog_grace;
+    }
+
+    // A limit of 0 removes it.
+    if let Some(memory_limit) = memory_limit {
+        config.cgroup_memory_max_mb = Some(*memory_limit).filter(|limit| *limit > 0);
+    }
+    if let Some(cpu_limit) = cpu_limit {
+        config.cgroup_cpu_max = Some(*cpu_limit).filter(|limit| *limit > 0.0);
+    }
+    if let Some(pids_limit) = pids_limit {
+        config.cgroup_pids_max = Some(*pids_limit).filter(|limit| *limit > 0);
+    }
+
+    if let Some(metrics_address) = metrics_address {
+        config.metrics_address =
+            Some(metrics_address.clone()).filter(|address| !address.is_empty());
+    }
+
+    if let Some(remote_address) = remote_address {
+        config.remote_address = Some(remote_address.clone()).filter(|address| !address.is_empty());
+    }
+    if let Some(remote_token) = remote_token {
+        config.remote_token = Some(remote_token.clone()).filter(|token| !token.is_empty());
+    }
+
+    config.remote_tls = remote_tls;
+
+    // Remote control is useless without a token, so one is generated when none was given.
+    let generated = config.remote_address.is_some() && config.remote_token.is_none();
+    if generated {
+        config.remote_token = Some(
+            rand::rng()
+                .sample_iter(&Alphanumeric)
+                .take(32)
+                .map(char::from)
+                .collect(),
+        );
+    }
+
     config.save();
 
     println!(
@@ -61,5 +188,14 @@ pub async fn config(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
         "DONE".green().bold()
     );
 
+    if generated && let Some(token) = &config.remote_token {
+        println!();
+        println!(
+            "{} {}",
+            "generated remote token:".bright_black(),
+            token.cyan()
+        );
+    }
+
     Ok(0)
 }
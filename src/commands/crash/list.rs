@@ -0,0 +1,55 @@
+use crate::{config, crash};
+
+use chrono::{DateTime, Local};
+use clap::ArgMatches;
+use colored::Colorize;
+
+pub async fn list(_matches: &ArgMatches) -> Result<i32, anyhow::Error> {
+    let _config = config::Config::new(".mcvcli.json", false);
+
+    let crashes = crash::crashes();
+
+    if crashes.is_empty() {
+        println!("{}", "no crashes found".red());
+        return Ok(1);
+    }
+
+    for (index, crash) in crashes.iter().enumerate() {
+        println!();
+        println!(
+            "{} ({})",
+            format!("#{}", index + 1).cyan().bold().underline(),
+            DateTime::from_timestamp(crash.time, 0)
+                .map(|time| time
+                    .with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string())
+                .unwrap_or_default()
+        );
+
+        println!(
+            "  {} {}",
+            "exit code:   ".bright_black(),
+            crash
+                .code
+                .map_or("unknown".to_string(), |code| code.to_string())
+                .cyan()
+        );
+        for (label, value) in crash.summary.fields() {
+            println!(
+                "  {} {}",
+                format!("{:<13}", format!("{label}:")).bright_black(),
+                value.cyan()
+            );
+        }
+    }
+
+    println!();
+    println!(
+        "{} {}",
+        "use".bright_black(),
+        "mcvcli crash show <number>".cyan()
+    );
+
+    Ok(0)
+}
@@ -0,0 +1,34 @@
+pub mod list;
+pub mod show;
+
+use crate::crash::Summary;
+
+use colored::Colorize;
+
+/// A crash summary as indented lines, for `status`, `attach`, `start` and `crash show`.
+pub fn format_summary(summary: &Summary) -> String {
+    let mut lines = Vec::new();
+
+    for (label, value) in summary.fields() {
+        lines.push(format!(
+            "  {} {}",
+            format!("{:<13}", format!("{label}:")).bright_black(),
+            value.cyan()
+        ));
+    }
+    for frame in &summary.frames {
+        lines.push(format!(
+            "    {} {}",
+            "at".bright_black(),
+            frame.bright_black()
+        ));
+    }
+    if lines.is_empty() {
+        lines.push(format!(
+            "  {}",
+            "no crash report or exception was found".bright_black()
+        ));
+    }
+
+    lines.join("\n")
+}
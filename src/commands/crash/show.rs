@@ -0,0 +1,61 @@
+use super::format_summary;
+use crate::{config, crash};
+
+use chrono::{DateTime, Local};
+use clap::ArgMatches;
+use colored::Colorize;
+
+pub async fn show(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
+    let number = *matches.get_one::<usize>("number").expect("required");
+    let full = *matches.get_one::<bool>("full").expect("required");
+    let _config = config::Config::new(".mcvcli.json", false);
+
+    let crashes = crash::crashes();
+    let Some(crash) = crashes.get(number.saturating_sub(1)) else {
+        println!(
+            "{} {} {}",
+            "crash".red(),
+            format!("#{number}").cyan(),
+            "does not exist, use mcvcli crash list".red()
+        );
+        return Ok(1);
+    };
+
+    if full {
+        let Some(report) = &crash.summary.report else {
+            println!(
+                "{}",
+                "the server did not write a crash report for this crash".red()
+            );
+            return Ok(1);
+        };
+
+        print!(
+            "{}",
+            std::fs::read_to_string(std::path::Path::new(crash::REPORTS_DIR).join(report))?
+        );
+        return Ok(0);
+    }
+
+    println!(
+        "{} ({})",
+        format!("#{number}").cyan().bold().underline(),
+        DateTime::from_timestamp(crash.time, 0)
+            .map(|time| time
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string())
+            .unwrap_or_default()
+    );
+    println!(
+        "  {} {}",
+        "exit code:   ".bright_black(),
+        crash
+            .code
+            .map_or("unknown".to_string(), |code| code.to_string())
+            .cyan()
+    );
+    println!("{}", format_summary(&crash.summary));
+
+    Ok(0)
+}
@@ -0,0 +1,159 @@
+use crate::detached;
+
+use chrono::Local;
+use flate2::{Compression, write::GzEncoder};
+use std::{
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::{Duration, SystemTime},
+};
+
+/// Writes the console log to `latest.log`. Once it reaches `cap` bytes (and whenever the daemon
+/// starts) the file is moved into the archive directory and gzipped in the background, keeping at
+/// most `retention` archives that are no older than `retention_days` (0 disables either limit).
+/// Archives are compressed and pruned one at a time on a single thread, so pruning never removes
+/// a log that is still being compressed.
+pub struct LogWriter {
+    file: Option<File>,
+    archiver: Option<mpsc::Sender<PathBuf>>,
+    written: u64,
+    total: u64,
+    cap: u64,
+    retention: usize,
+    retention_days: u64,
+}
+
+impl LogWriter {
+    pub fn new(cap: u64, retention: usize, retention_days: u64) -> Self {
+        let _ = std::fs::create_dir_all(detached::dir());
+
+        let mut writer = LogWriter {
+            file: None,
+            archiver: None,
+            written: 0,
+            total: 0,
+            cap,
+            retention,
+            retention_days,
+        };
+        writer.rotate();
+
+        writer
+    }
+
+    pub fn write(&mut self, data: &[u8]) {
+        if self.cap > 0 && self.written.saturating_add(data.len() as u64) > self.cap {
+            self.rotate();
+        }
+
+        if let Some(file) = self.file.as_mut()
+            && file.write_all(data).is_ok()
+        {
+            self.written += data.len() as u64;
+            self.total += data.len() as u64;
+        }
+    }
+
+    /// The amount of bytes written since the daemon started, across rotations.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    fn rotate(&mut self) {
+        self.file = None;
+
+        let has_content = detached::log_path()
+            .metadata()
+            .is_ok_and(|metadata| metadata.len() > 0);
+        if has_content
+            && let Some(archive) = archive_path()
+            && std::fs::rename(detached::log_path(), &archive).is_ok()
+        {
+            let archiver = self.archiver.get_or_insert_with(|| {
+                let (tx, rx) = mpsc::channel::<PathBuf>();
+                let retention = self.retention;
+                let retention_days = self.retention_days;
+
+                std::thread::spawn(move || {
+                    for archive in rx {
+                        compress(&archive);
+                        prune(retention, retention_days);
+                    }
+                });
+
+                tx
+            });
+            let _ = archiver.send(archive);
+        }
+
+        self.file = File::create(detached::log_path()).ok();
+        self.written = 0;
+    }
+}
+
+fn archive_path() -> Option<PathBuf> {
+    let directory = detached::log_archive_dir();
+    std::fs::create_dir_all(&directory).ok()?;
+
+    let stamp = Local::now().format("%Y-%m-%d-%H%M%S").to_string();
+    let mut name = stamp.clone();
+    let mut index = 1;
+    while directory.join(format!("{name}.log")).exists()
+        || directory.join(format!("{name}.log.gz")).exists()
+    {
+        name = format!("{stamp}-{index}");
+        index += 1;
+    }
+
+    Some(directory.join(format!("{name}.log")))
+}
+
+fn compress(path: &Path) {
+    let target = path.with_extension("log.gz");
+
+    let result = (|| -> std::io::Result<()> {
+        let mut source = File::open(path)?;
+        let mut encoder = GzEncoder::new(File::create(&target)?, Compression::default());
+        std::io::copy(&mut source, &mut encoder)?;
+        encoder.finish()?.sync_all()
+    })();
+
+    match result {
+        Ok(()) => {
+            let _ = std::fs::remove_file(path);
+        }
+        Err(_) => {
+            let _ = std::fs::remove_file(&target);
+        }
+    }
+}
+
+fn prune(retention: usize, retention_days: u64) {
+    let mut archives = detached::log_archives();
+
+    if retention_days > 0 {
+        let max_age = Duration::from_secs(retention_days * 24 * 60 * 60);
+
+        archives.retain(|archive| {
+            let expired = archive
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+                .is_some_and(|age| age > max_age);
+            if expired {
+                let _ = std::fs::remove_file(archive);
+            }
+
+            !expired
+        });
+    }
+
+    if retention > 0 && archives.len() > retention {
+        for archive in &archives[..archives.len() - retention] {
+            let _ = std::fs::remove_file(archive);
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use super::Shared;
+
+use axum::{Router, extract::State, http::header, response::IntoResponse, routing::get};
+use std::{
+    fmt::Write,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::{TcpListener, TcpStream},
+    sync::Mutex,
+};
+
+/// Scrapes take milliseconds, Prometheus simply reconnects for the next one.
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(30);
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn metric(body: &mut String, name: &str, kind: &str, help: &str, samples: &[(String, f64)]) {
+    let _ = writeln!(body, "# HELP {name} {help}");
+    let _ = writeln!(body, "# TYPE {name} {kind}");
+
+    for (labels, value) in samples {
+        let _ = writeln!(body, "{name}{{{labels}}} {value}");
+    }
+}
+
+/// Renders the metrics in the Prometheus text exposition format.
+async fn render(shared: &Mutex<Shared>, system: &mut sysinfo::System, profile: &str) -> String {
+    let labels = format!("profile=\"{}\"", escape(profile));
+    let (java_pid, ready, players, restarts, exits, log_bytes) = {
+        let shared = shared.lock().await;
+        (
+            shared.java_pid,
+            shared.ready_at.is_some(),
+            shared.players.len(),
+            shared.restarts,
+            shared.exits.clone(),
+            shared.log.total(),
+        )
+    };
+
+    let pid = sysinfo::Pid::from(java_pid as usize);
+    system.refresh_processes_specifics(
+        sysinfo::ProcessesToUpdate::Some(&[pid]),
+        true,
+        sysinfo::ProcessRefreshKind::nothing()
+            .with_cpu()
+            .with_memory(),
+    );
+    let process = system.process(pid).filter(|_| java_pid != 0);
+
+    let mut body = String::new();
+    metric(
+        &mut body,
+        "mcvcli_up",
+        "gauge",
+        "Whether the server process is running.",
+        &[(labels.clone(), process.is_some() as u8 as f64)],
+    );
+    metric(
+        &mut body,
+        "mcvcli_ready",
+        "gauge",
+        "Whether the server has finished starting.",
+        &[(labels.clone(), ready as u8 as f64)],
+    );
+
+    if let Some(process) = process {
+        metric(
+            &mut body,
+            "mcvcli_process_cpu_usage_percent",
+            "gauge",
+            "CPU usage of the server process since the previous scrape, in percent of one core.",
+            &[(labels.clone(), process.cpu_usage() as f64)],
+        );
+        metric(
+            &mut body,
+            "mcvcli_process_resident_memory_bytes",
+            "gauge",
+            "Resident memory of the server process.",
+            &[(labels.clone(), process.memory() as f64)],
+        );
+        metric(
+            &mut body,
+            "mcvcli_process_uptime_seconds",
+            "gauge",
+            "Time since the server process started.",
+            &[(labels.clone(), process.run_time() as f64)],
+        );
+    }
+
+    metric(
+        &mut body,
+        "mcvcli_players_online",
+        "gauge",
+        "Players online, as seen in the console output.",
+        &[(labels.clone(), players as f64)],
+    );
+    metric(
+        &mut body,
+        "mcvcli_restarts_total",
+        "counter",
+        "Restarts of the server process by the daemon.",
+        &[(labels.clone(), restarts as f64)],
+    );
+    metric(
+        &mut body,
+        "mcvcli_exits_total",
+        "counter",
+        "Exits of the server process, by exit code.",
+        &exits
+            .iter()
+            .map(|(code, count)| (format!("{labels},code=\"{code}\""), *count as f64))
+            .collect::<Vec<_>>(),
+    );
+    metric(
+        &mut body,
+        "mcvcli_log_bytes_written_total",
+        "counter",
+        "Console output written to the daemon log.",
+        &[(labels, log_bytes as f64)],
+    );
+
+    body
+}
+
+/// Accepted connections only live for [`CONNECTION_TIMEOUT`], so clients that never finish (or
+/// never stop sending) their request can't pile up.
+struct Listener(TcpListener);
+
+impl axum::serve::Listener for Listener {
+    type Io = Deadline;
+    type Addr = std::net::SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        loop {
+            match self.0.accept().await {
+                Ok((stream, address)) => {
+                    return (
+                        Deadline {
+                            stream,
+                            deadline: Box::pin(tokio::time::sleep(CONNECTION_TIMEOUT)),
+                        },
+                        address,
+                    );
+                }
+                // Like running out of file descriptors, which may go away again.
+                Err(_) => tokio::time::sleep(Duration::from_millis(100)).await,
+            }
+        }
+    }
+
+    fn local_addr(&self) -> std::io::Result<Self::Addr> {
+        self.0.local_addr()
+    }
+}
+
+struct Deadline {
+    stream: TcpStream,
+    deadline: Pin<Box<tokio::time::Sleep>>,
+}
+
+impl Deadline {
+    fn expired(&mut self, cx: &mut Context<'_>) -> bool {
+        self.deadline.as_mut().poll(cx).is_ready()
+    }
+}
+
+fn timed_out<T>() -> Poll<std::io::Result<T>> {
+    Poll::Ready(Err(std::io::ErrorKind::TimedOut.into()))
+}
+
+impl AsyncRead for Deadline {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        if self.expired(cx) {
+            return timed_out();
+        }
+
+        Pin::new(&mut self.stream).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for Deadline {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        if self.expired(cx) {
+            return timed_out();
+        }
+
+        Pin::new(&mut self.stream).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.stream).poll_shutdown(cx)
+    }
+}
+
+#[derive(Clone)]
+struct Metrics {
+    shared: Arc<Mutex<Shared>>,
+    system: Arc<Mutex<sysinfo::System>>,
+    profile: Arc<String>,
+}
+
+async fn metrics(State(metrics): State<Metrics>) -> impl IntoResponse {
+    let body = render(
+        &metrics.shared,
+        &mut *metrics.system.lock().await,
+        &metrics.profile,
+    )
+    .await;
+
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body)
+}
+
+/// Serves `GET /metrics` for Prometheus on the listener until the daemon exits.
+pub async fn serve(listener: TcpListener, shared: Arc<Mutex<Shared>>, profile: String) {
+    let app = Router::new()
+        .route("/metrics", get(metrics))
+        .with_state(Metrics {
+            shared,
+            system: Arc::new(Mutex::new(sysinfo::System::new())),
+            profile: Arc::new(profile),
+        });
+
+    let _ = axum::serve(Listener(listener), app).await;
+}
@@ -0,0 +1,1522 @@
+mod log;
+mod metrics;
+mod process;
+mod remote;
+mod watchdog;
+
+use crate::{
+    backups, cgroup, config, crash,
+    detached::{
+        self, DaemonStatus, ExecRequest, Identity, ProcessStatus, RestartNotice, Spec, State,
+        StopRequest,
+    },
+    hooks::{self, Hook},
+    logs::{self, Event},
+    properties, rcon, registry, schedule,
+    tls::Tls,
+    usage,
+    webhooks::{self, Notification},
+};
+
+use chrono::Timelike;
+use interprocess::local_socket::{GenericNamespaced, ListenerOptions, ToNsName, tokio::prelude::*};
+use log::LogWriter;
+use process::{Server, Stdin};
+use rand::{RngExt, distr::Alphanumeric};
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    io::Write,
+    process::{ExitStatus, Stdio},
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
+};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite},
+    process::Child,
+    sync::{Mutex, mpsc, oneshot},
+    task::AbortHandle,
+};
+
+/// Everything a client connection (or the scheduler) needs to talk to the supervisor.
+#[derive(Clone)]
+struct Context {
+    shared: Arc<Mutex<Shared>>,
+    stdin_tx: mpsc::UnboundedSender<Vec<u8>>,
+    control_tx: mpsc::UnboundedSender<Control>,
+    exec_lock: Arc<Mutex<()>>,
+}
+
+impl Context {
+    /// Writes to the console once a running `exec` is done, so its capture window only holds the
+    /// response to its own command.
+    async fn console(&self, bytes: Vec<u8>) {
+        let _guard = self.exec_lock.lock().await;
+        let _ = self.stdin_tx.send(bytes);
+    }
+}
+
+enum Control {
+    Stop(u64),
+    /// Carries the spec to respawn the server with, if the restart came with one.
+    Restart(u64, Option<Box<Spec>>),
+}
+
+/// How the server process ended, as seen by the supervisor.
+enum Exit {
+    Exited,
+    Stopped,
+    Restarted,
+}
+
+/// A server that stayed up at least this long before exiting starts a fresh backoff sequence.
+const STABLE_UPTIME: Duration = Duration::from_secs(300);
+const MAX_BACKOFF_SECS: u64 = 300;
+/// How long the daemon lingers after the server is gone to deliver the last webhooks.
+const WEBHOOK_DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+struct Shared {
+    ring: VecDeque<(u8, Vec<u8>)>,
+    ring_bytes: usize,
+    ring_cap: usize,
+    clients: Vec<mpsc::UnboundedSender<(u8, Vec<u8>)>>,
+    captures: Vec<mpsc::UnboundedSender<Vec<u8>>>,
+    log: LogWriter,
+    spec: Spec,
+    stop_requested: bool,
+
+    line: Vec<u8>,
+    /// The latest output of the current java process, to look for an exception if it crashes.
+    session: Vec<u8>,
+    players: BTreeSet<String>,
+    ready_at: Option<i64>,
+
+    java_pid: u32,
+    restarts: u64,
+    exits: BTreeMap<i32, u64>,
+
+    countdown: Option<(AbortHandle, &'static str)>,
+    /// Fired when the current java process has finished loading, to run the post-start hook.
+    ready: Option<oneshot::Sender<()>>,
+    /// Webhook notifications for `dispatch`, dropped when the daemon is done.
+    notifications: Option<mpsc::UnboundedSender<Notification>>,
+    /// What hooks outside the supervisor loop, like those of scheduled backups, are told about
+    /// the current java process.
+    environment: hooks::Environment,
+}
+
+impl Shared {
+    fn new(log: LogWriter, spec: Spec, notifications: mpsc::UnboundedSender<Notification>) -> Self {
+        Shared {
+            ring: VecDeque::new(),
+            ring_bytes: 0,
+            ring_cap: 256 * 1024,
+            clients: Vec::new(),
+            captures: Vec::new(),
+            log,
+            spec,
+            stop_requested: false,
+            line: Vec::new(),
+            session: Vec::new(),
+            players: BTreeSet::new(),
+            ready_at: None,
+            java_pid: 0,
+            restarts: 0,
+            exits: BTreeMap::new(),
+            countdown: None,
+            ready: None,
+            notifications: Some(notifications),
+            environment: hooks::Environment::default(),
+        }
+    }
+
+    fn notify(&self, notification: Notification) {
+        if let Some(notifications) = &self.notifications {
+            let _ = notifications.send(notification);
+        }
+    }
+
+    /// Forgets everything learned about the previous java process.
+    fn reset(&mut self) {
+        self.stop_requested = false;
+        self.line.clear();
+        self.session.clear();
+        self.players.clear();
+        self.ready_at = None;
+    }
+
+    fn status(&self) -> DaemonStatus {
+        DaemonStatus {
+            ready_at: self.ready_at,
+            players: self.players.iter().cloned().collect(),
+            process: None,
+        }
+    }
+
+    /// Splits stdout into lines and turns the ones that are recognised into events.
+    fn parse_events(&mut self, data: &[u8]) {
+        self.line.extend_from_slice(data);
+
+        while let Some(position) = self.line.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.line.drain(..=position).collect();
+            let Some(event) = logs::event(&String::from_utf8_lossy(&line)) else {
+                continue;
+            };
+
+            match &event {
+                Event::Ready { startup_secs } => {
+                    self.ready_at = Some(chrono::Utc::now().timestamp());
+                    if let Some(ready) = self.ready.take() {
+                        let _ = ready.send(());
+                    }
+                    self.notify(
+                        Notification::new(
+                            webhooks::Event::Ready,
+                            format!("server is ready after {startup_secs:.1}s"),
+                        )
+                        .field("startup_secs", startup_secs),
+                    );
+                }
+                Event::Join { player } => {
+                    self.players.insert(player.clone());
+                    self.notify(
+                        Notification::new(webhooks::Event::Join, format!("{player} joined"))
+                            .field("player", player),
+                    );
+                }
+                Event::Leave { player } => {
+                    self.players.remove(player);
+                    self.notify(
+                        Notification::new(webhooks::Event::Leave, format!("{player} left"))
+                            .field("player", player),
+                    );
+                }
+                _ => {}
+            }
+
+            let payload = serde_json::to_vec(&event).unwrap_or_default();
+            self.clients
+                .retain(|client| client.send((detached::TAG_EVENT, payload.clone())).is_ok());
+        }
+
+        // A server printing without newlines shouldn't grow the buffer forever.
+        if self.line.len() > 64 * 1024 {
+            self.line.clear();
+        }
+    }
+
+    fn push(&mut self, tag: u8, data: Vec<u8>) {
+        self.log.write(&data);
+
+        if tag == detached::TAG_STDOUT {
+            self.parse_events(&data);
+        }
+
+        self.session.extend_from_slice(&data);
+        if self.session.len() > 64 * 1024 {
+            let excess = self.session.len() - 64 * 1024;
+            self.session.drain(..excess);
+        }
+
+        self.clients
+            .retain(|client| client.send((tag, data.clone())).is_ok());
+        self.captures
+            .retain(|capture| capture.send(data.clone()).is_ok());
+
+        self.ring_bytes += data.len();
+        self.ring.push_back((tag, data));
+        while self.ring_bytes > self.ring_cap {
+            match self.ring.pop_front() {
+                Some((_, dropped)) => self.ring_bytes -= dropped.len(),
+                None => break,
+            }
+        }
+    }
+
+    fn register(&mut self, client: mpsc::UnboundedSender<(u8, Vec<u8>)>) {
+        for (tag, data) in &self.ring {
+            let _ = client.send((*tag, data.clone()));
+        }
+        let _ = client.send((detached::TAG_REPLAY_END, Vec::new()));
+
+        self.clients.push(client);
+    }
+
+    fn broadcast_restart(&mut self, notice: &RestartNotice) {
+        self.restarts += 1;
+
+        let message = if notice.requested {
+            format!("server stopped with code {}, restarting", notice.code)
+        } else {
+            format!(
+                "server exited with code {}, restarting in {}s (attempt {}/{})",
+                notice.code, notice.delay_secs, notice.attempt, notice.max_retries
+            )
+        };
+        self.log.write(format!("\n[mcvcli] {message}\n").as_bytes());
+        self.notify(
+            Notification::new(webhooks::Event::Restart, message)
+                .field("code", notice.code)
+                .field("attempt", notice.attempt),
+        );
+
+        let payload = serde_json::to_vec(notice).unwrap_or_default();
+        self.clients.retain(|client| {
+            client
+                .send((detached::TAG_RESTART, payload.clone()))
+                .is_ok()
+        });
+    }
+
+    /// Writes a crash summary to the log and sends it to attached clients.
+    fn report_crash(&mut self, code: i32, summary: &crash::Summary) {
+        let mut notification = Notification::new(
+            webhooks::Event::Crash,
+            match &summary.exception {
+                Some(exception) => format!("server crashed with code {code}: {exception}"),
+                None => format!("server crashed with code {code}"),
+            },
+        )
+        .field("code", code);
+        for (label, value) in summary.fields() {
+            notification = notification.field(label, value);
+        }
+        self.notify(notification);
+
+        let mut message = String::from("\n[mcvcli] server crashed\n");
+        for (label, value) in summary.fields() {
+            message.push_str(&format!("[mcvcli]   {label}: {value}\n"));
+        }
+        for frame in &summary.frames {
+            message.push_str(&format!("[mcvcli]     at {frame}\n"));
+        }
+        self.log.write(message.as_bytes());
+
+        let payload = serde_json::to_vec(summary).unwrap_or_default();
+        self.clients
+            .retain(|client| client.send((detached::TAG_CRASH, payload.clone())).is_ok());
+    }
+
+    fn broadcast_exit(&mut self, code: i32) {
+        let payload = code.to_be_bytes().to_vec();
+        self.clients
+            .retain(|client| client.send((detached::TAG_EXIT, payload.clone())).is_ok());
+    }
+}
+
+/// Spawns java, inside the cgroup whose `cgroup.procs` is given. The child moves itself in before
+/// it execs, so the server never runs without its limits.
+fn spawn_java(spec: &Spec, stdin: Stdio, cgroup: Option<&std::fs::File>) -> std::io::Result<Child> {
+    let mut command = tokio::process::Command::new(&spec.binary);
+    command
+        .args(&spec.extra_flags)
+        .arg(format!("-Xmx{}M", spec.ram_mb))
+        .arg("-jar")
+        .arg(&spec.jar_file)
+        .arg("nogui")
+        .args(&spec.extra_args)
+        .env("JAVA_HOME", &spec.java_home)
+        .stdin(stdin)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    #[cfg(unix)]
+    if let Some(procs) = cgroup {
+        use std::os::fd::AsRawFd;
+
+        let fd = procs.as_raw_fd();
+        // SAFETY: write(2) is async-signal-safe, and writing 0 moves the calling process.
+        unsafe {
+            command.pre_exec(move || {
+                if libc::write(fd, b"0".as_ptr().cast(), 1) < 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+
+                Ok(())
+            });
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = cgroup;
+
+    command.spawn()
+}
+
+fn pipe_output<R: AsyncRead + Unpin + Send + 'static>(
+    shared: &Arc<Mutex<Shared>>,
+    mut reader: R,
+    tag: u8,
+) {
+    let shared = Arc::clone(shared);
+    tokio::spawn(async move {
+        let mut buffer = [0u8; 8192];
+        loop {
+            match reader.read(&mut buffer).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => shared.lock().await.push(tag, buffer[..n].to_vec()),
+            }
+        }
+    });
+}
+
+/// Runs a hook with its output going to the log, returns whether all of its commands succeeded.
+async fn run_hook(
+    shared: &Arc<Mutex<Shared>>,
+    hook: Hook,
+    environment: &hooks::Environment,
+) -> bool {
+    let hooks = shared.lock().await.spec.hooks.clone();
+    if hooks.commands(hook).is_empty() {
+        return true;
+    }
+
+    shared
+        .lock()
+        .await
+        .log
+        .write(format!("\n[mcvcli] running {} hooks\n", hook.name()).as_bytes());
+
+    let outcome = hooks::run(hook, &hooks, environment).await;
+
+    let mut shared = shared.lock().await;
+    shared.log.write(&outcome.output);
+    match outcome.result {
+        Ok(()) => true,
+        Err(err) => {
+            shared.log.write(
+                format!("\n[mcvcli] warning: {} hook failed: {err:#}\n", hook.name()).as_bytes(),
+            );
+            false
+        }
+    }
+}
+
+/// The exit code and whether it was a success for an adopted server, whose real exit status
+/// can't be known as it isn't a child of the supervisor. It exited cleanly if it was asked to
+/// stop, or logged its shutdown without writing a crash report; anything else (a crash, a kill
+/// or the OOM killer) counts as a failure with code 1.
+fn adopted_outcome(exit: &Exit, stop_requested: bool, started_at: SystemTime) -> (i32, bool) {
+    let requested = stop_requested || !matches!(exit, Exit::Exited);
+    // The server's own log rather than the tail of it, which may not have caught up yet.
+    let shut_down = crash::server_log_since(started_at).contains("Stopping server")
+        && crash::report_since(started_at).is_none();
+
+    if requested || shut_down {
+        (0, true)
+    } else {
+        (1, false)
+    }
+}
+
+/// Writes the spec a restart came with to disk, once the restart goes ahead, for the respawn to
+/// pick up.
+async fn apply_spec(shared: &Arc<Mutex<Shared>>, spec: Option<Box<Spec>>) {
+    let Some(spec) = spec else {
+        return;
+    };
+
+    if let Err(err) = detached::write_spec(&spec) {
+        shared.lock().await.log.write(
+            format!("\n[mcvcli] warning: failed to apply the new config: {err}\n").as_bytes(),
+        );
+    }
+}
+
+/// Waits for the server to exit, either on its own or after a stop or restart request.
+async fn supervise(
+    server: &mut Server,
+    control_rx: &mut mpsc::UnboundedReceiver<Control>,
+    stdin_tx: &mpsc::UnboundedSender<Vec<u8>>,
+    stop_command: &str,
+    shared: &Arc<Mutex<Shared>>,
+    environment: &hooks::Environment,
+) -> (Option<ExitStatus>, Exit) {
+    tokio::select! {
+        status = server.wait() => (status, Exit::Exited),
+        Some(control) = control_rx.recv() => {
+            let (timeout, exit) = match control {
+                Control::Stop(timeout) => (timeout, Exit::Stopped),
+                Control::Restart(timeout, new) => {
+                    apply_spec(shared, new).await;
+                    (timeout, Exit::Restarted)
+                }
+            };
+
+            // The pre-stop hook runs on the stop timeout, so a slow hook can't hold up the stop.
+            let deadline = tokio::time::Instant::now() + Duration::from_secs(timeout);
+            if tokio::time::timeout_at(deadline, run_hook(shared, Hook::PreStop, environment))
+                .await
+                .is_err()
+            {
+                shared.lock().await.log.write(
+                    b"\n[mcvcli] warning: pre-stop hooks did not finish before the stop timeout\n",
+                );
+            }
+
+            if !server.terminate() {
+                let _ = stdin_tx.send(format!("{stop_command}\n").into_bytes());
+            }
+
+            let status = tokio::select! {
+                status = server.wait() => status,
+                _ = tokio::time::sleep_until(deadline) => server.kill().await,
+            };
+
+            (status, exit)
+        }
+    }
+}
+
+impl Control {
+    fn action(&self) -> &'static str {
+        match self {
+            Control::Stop(_) => "stop",
+            Control::Restart(..) => "restart",
+        }
+    }
+}
+
+fn format_time(seconds: u64) -> String {
+    match seconds {
+        1 => "1 second".to_string(),
+        60 => "1 minute".to_string(),
+        seconds if seconds % 60 == 0 => format!("{} minutes", seconds / 60),
+        seconds => format!("{seconds} seconds"),
+    }
+}
+
+/// Warns players at the configured intervals, then hands the stop or restart to the supervisor.
+async fn countdown(context: Context, seconds: u64, control: Control) {
+    let action = control.action();
+    let settings = context.shared.lock().await.spec.countdown.clone();
+    let announce = |remaining: u64| {
+        let message = settings
+            .message
+            .replace("{action}", action)
+            .replace("{time}", &format_time(remaining));
+        context.console(format!("{message}\n").into_bytes())
+    };
+
+    if settings.save {
+        context.console(b"save-all\n".to_vec()).await;
+    }
+
+    let mut intervals: Vec<u64> = settings
+        .intervals
+        .iter()
+        .copied()
+        .filter(|interval| *interval < seconds)
+        .collect();
+    intervals.sort_unstable_by(|a, b| b.cmp(a));
+    intervals.dedup();
+
+    let mut remaining = seconds;
+    announce(remaining).await;
+    for interval in intervals {
+        tokio::time::sleep(Duration::from_secs(remaining - interval)).await;
+        remaining = interval;
+        announce(remaining).await;
+    }
+    tokio::time::sleep(Duration::from_secs(remaining)).await;
+
+    context.shared.lock().await.countdown = None;
+    let _ = context.control_tx.send(control);
+}
+
+/// Starts a countdown in the background, replacing one that is already running.
+async fn start_countdown(context: &Context, seconds: u64, control: Control) {
+    let action = control.action();
+    let handle = tokio::spawn(countdown(context.clone(), seconds, control));
+
+    let mut shared = context.shared.lock().await;
+    shared.log.write(
+        format!(
+            "\n[mcvcli] server will {action} in {}\n",
+            format_time(seconds)
+        )
+        .as_bytes(),
+    );
+    if let Some((previous, _)) = shared.countdown.replace((handle.abort_handle(), action)) {
+        previous.abort();
+    }
+}
+
+/// Aborts the running countdown, telling players about it. Returns whether one was running.
+async fn cancel_countdown(context: &Context) -> bool {
+    let message = {
+        let mut shared = context.shared.lock().await;
+        let Some((handle, action)) = shared.countdown.take() else {
+            return false;
+        };
+
+        handle.abort();
+        shared
+            .log
+            .write(format!("\n[mcvcli] server {action} cancelled\n").as_bytes());
+
+        shared
+            .spec
+            .countdown
+            .cancel_message
+            .replace("{action}", action)
+    };
+    context.console(format!("{message}\n").into_bytes()).await;
+
+    true
+}
+
+/// Stops or restarts the server, right away or after a countdown. Doing it right away overrides a
+/// running countdown.
+async fn request(context: &Context, countdown: u64, control: Control) {
+    if countdown > 0 {
+        start_countdown(context, countdown, control).await;
+        return;
+    }
+
+    if let Some((handle, _)) = context.shared.lock().await.countdown.take() {
+        handle.abort();
+    }
+    let _ = context.control_tx.send(control);
+}
+
+async fn run_job(job: schedule::Job, context: &Context) {
+    context.shared.lock().await.log.write(
+        format!(
+            "\n[mcvcli] running scheduled job {}: {}\n",
+            job.id,
+            job.action.describe()
+        )
+        .as_bytes(),
+    );
+
+    match job.action {
+        schedule::Action::Command { command } => {
+            context.console(format!("{command}\n").into_bytes()).await;
+        }
+        schedule::Action::Restart { timeout, countdown } => {
+            request(context, countdown, Control::Restart(timeout, None)).await;
+        }
+        schedule::Action::Backup { format } => {
+            let name = format!("scheduled-{}", chrono::Local::now().format("%Y%m%d-%H%M%S"));
+
+            // A running server keeps writing region files, so it is told to flush and pause
+            // saving for the duration of the backup. That needs a server that has finished
+            // loading; one that is still starting can be backed up neither way.
+            let (running, ready) = {
+                let shared = context.shared.lock().await;
+                (shared.java_pid != 0, shared.ready_at.is_some())
+            };
+            if running && !ready {
+                context.shared.lock().await.log.write(
+                    format!(
+                        "\n[mcvcli] scheduled backup {name} skipped, the server is still starting\n"
+                    )
+                    .as_bytes(),
+                );
+                return;
+            }
+
+            let environment = hooks::Environment {
+                backup: Some(name.clone()),
+                ..context.shared.lock().await.environment.clone()
+            };
+            if !run_hook(&context.shared, Hook::PreBackup, &environment).await {
+                context.shared.lock().await.log.write(
+                    format!(
+                        "\n[mcvcli] scheduled backup {name} skipped, its pre-backup hook failed\n"
+                    )
+                    .as_bytes(),
+                );
+                return;
+            }
+
+            let result = if ready {
+                backups::create_live(&name, format, |mut request| async move {
+                    request.identity = Some(Identity::current(
+                        Some("scheduled backup".to_string()),
+                        false,
+                    ));
+                    Ok(exec(context, request, None).await)
+                })
+                .await
+            } else {
+                let name = name.clone();
+                tokio::task::spawn_blocking(move || backups::create(&name, &format))
+                    .await
+                    .map_err(anyhow::Error::from)
+                    .and_then(|result| result)
+            };
+
+            if result.is_ok() {
+                run_hook(&context.shared, Hook::PostBackup, &environment).await;
+            }
+
+            let mut shared = context.shared.lock().await;
+            let message = match result {
+                Ok(()) => {
+                    shared.notify(
+                        Notification::new(
+                            webhooks::Event::Backup,
+                            format!("scheduled backup {name} created"),
+                        )
+                        .field("name", &name),
+                    );
+                    format!("\n[mcvcli] scheduled backup {name} created\n")
+                }
+                Err(err) => format!("\n[mcvcli] scheduled backup failed: {err}\n"),
+            };
+            shared.log.write(message.as_bytes());
+        }
+    }
+}
+
+/// Delivers webhook notifications in the background, so a slow or unreachable endpoint never
+/// holds up the supervisor. The webhooks come from the spec, which `mcvcli restart` re-reads.
+async fn dispatch(
+    shared: Arc<Mutex<Shared>>,
+    profile: String,
+    mut notifications: mpsc::UnboundedReceiver<Notification>,
+) {
+    let mut deliveries = tokio::task::JoinSet::new();
+
+    while let Some(notification) = notifications.recv().await {
+        while deliveries.try_join_next().is_some() {}
+
+        let hooks = shared.lock().await.spec.webhooks.clone();
+        if hooks.is_empty() {
+            continue;
+        }
+
+        let shared = Arc::clone(&shared);
+        let profile = profile.clone();
+        deliveries.spawn(async move {
+            for (host, err) in webhooks::send(&hooks, &profile, &notification).await {
+                shared.lock().await.log.write(
+                    format!("\n[mcvcli] warning: webhook to {host} failed: {err:#}\n").as_bytes(),
+                );
+            }
+        });
+    }
+
+    while deliveries.join_next().await.is_some() {}
+}
+
+/// Wakes up at the start of every minute and runs the jobs from the schedule file that are due.
+/// The file is re-read on every tick so `mcvcli schedule` changes apply without a restart.
+async fn scheduler(context: Context) {
+    let mut read_error = None;
+
+    loop {
+        let now = chrono::Local::now();
+        let until_next_minute = Duration::from_secs(60 - now.second() as u64)
+            .saturating_sub(Duration::from_nanos(now.nanosecond() as u64));
+        tokio::time::sleep(until_next_minute).await;
+
+        let jobs = match schedule::read() {
+            Ok(jobs) => {
+                read_error = None;
+                jobs
+            }
+            Err(err) => {
+                // Only logged once per problem, not on every tick.
+                let message = format!("{err:#}");
+                if read_error.as_ref() != Some(&message) {
+                    context.shared.lock().await.log.write(
+                        format!("\n[mcvcli] warning: no scheduled jobs run, {message}\n")
+                            .as_bytes(),
+                    );
+                    read_error = Some(message);
+                }
+                continue;
+            }
+        };
+
+        let now = chrono::Local::now();
+        for job in jobs {
+            match schedule::Cron::parse(&job.expression) {
+                Ok(cron) if cron.matches(&now) => {
+                    let context = context.clone();
+                    tokio::spawn(async move {
+                        run_job(job, &context).await;
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Sends a console command and collects everything the server prints until it has been quiet
+/// for `quiet_ms` (or printed one of `until`) or `timeout_ms` has passed. Console input from
+/// mcvcli (other execs, attached consoles, scheduled commands and countdowns) waits for the window
+/// to close, so it only contains the response to this command and whatever the server logs on
+/// its own in the meantime, like chat or player joins.
+async fn exec(context: &Context, request: ExecRequest, peer: Option<&str>) -> Vec<u8> {
+    let _guard = context.exec_lock.lock().await;
+
+    audit(request.identity.as_ref(), peer, request.command.as_bytes());
+
+    let (tx, mut rx) = mpsc::unbounded_channel::<Vec<u8>>();
+    {
+        let mut shared = context.shared.lock().await;
+        if is_stop_command(request.command.as_bytes(), &shared.spec.stop_command) {
+            shared.stop_requested = true;
+        }
+
+        shared.captures.push(tx);
+    }
+
+    let _ = context
+        .stdin_tx
+        .send(format!("{}\n", request.command.trim_end()).into_bytes());
+
+    let mut output = Vec::new();
+    let deadline = tokio::time::Instant::now() + Duration::from_millis(request.timeout_ms);
+    let quiet = Duration::from_millis(request.quiet_ms);
+
+    loop {
+        // Wait the full window for the first output, then only as long as the server keeps talking.
+        let wait = if output.is_empty() || !request.until.is_empty() {
+            deadline
+        } else {
+            (tokio::time::Instant::now() + quiet).min(deadline)
+        };
+
+        match tokio::time::timeout_at(wait, rx.recv()).await {
+            Ok(Some(data)) => output.extend_from_slice(&data),
+            Ok(None) | Err(_) => break,
+        }
+
+        let text = String::from_utf8_lossy(&output);
+        if request
+            .until
+            .iter()
+            .any(|until| text.contains(until.as_str()))
+        {
+            break;
+        }
+    }
+
+    output
+}
+
+fn is_stop_command(payload: &[u8], stop_command: &str) -> bool {
+    String::from_utf8_lossy(payload)
+        .lines()
+        .any(|line| line.trim() == stop_command)
+}
+
+/// The java process, for remote clients that can't look it up themselves.
+fn process_status(pid: u32) -> Option<ProcessStatus> {
+    let pid = sysinfo::Pid::from(pid as usize);
+    let mut system = sysinfo::System::new();
+    system.refresh_processes_specifics(
+        sysinfo::ProcessesToUpdate::Some(&[pid]),
+        true,
+        sysinfo::ProcessRefreshKind::nothing().with_memory(),
+    );
+
+    let process = system.process(pid)?;
+
+    Some(ProcessStatus {
+        pid: pid.as_u32(),
+        memory: process.memory(),
+        start_time: process.start_time(),
+    })
+}
+
+/// Appends console input to the audit log, one line per command, with who sent it (and from
+/// where, for remote clients).
+fn audit(identity: Option<&Identity>, peer: Option<&str>, payload: &[u8]) {
+    let Ok(mut file) = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(detached::audit_path())
+    else {
+        return;
+    };
+
+    let time = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
+    let mut identity = identity.map_or("unknown".to_string(), Identity::to_string);
+    if let Some(peer) = peer {
+        identity.push_str(&format!(" from {peer}"));
+    }
+
+    for line in String::from_utf8_lossy(payload).lines() {
+        if !line.trim().is_empty() {
+            let _ = writeln!(file, "[{time}] {identity}: {line}");
+        }
+    }
+}
+
+/// Replaces the user a client claims to be with the one the operating system reports, if known.
+fn verified(identity: Option<Identity>, user: Option<&str>) -> Option<Identity> {
+    let Some(user) = user else {
+        return identity;
+    };
+
+    let host = sysinfo::System::host_name().unwrap_or_else(|| "unknown".to_string());
+    Some(match identity {
+        Some(identity) => Identity {
+            user: user.to_string(),
+            host,
+            ..identity
+        },
+        None => Identity {
+            user: user.to_string(),
+            host,
+            label: None,
+            read_only: false,
+        },
+    })
+}
+
+/// The name of the user on the other end of the local socket, from its peer credentials.
+#[cfg(unix)]
+fn peer_user(connection: &LocalSocketStream) -> Option<String> {
+    let uid = connection.peer_creds().ok()?.euid()?;
+    let uid = sysinfo::Uid::try_from(uid as usize).ok()?;
+
+    sysinfo::Users::new_with_refreshed_list()
+        .get_user_by_id(&uid)
+        .map(|user| user.name().to_string())
+        .or_else(|| Some(format!("uid {}", *uid)))
+}
+
+#[cfg(not(unix))]
+fn peer_user(_connection: &LocalSocketStream) -> Option<String> {
+    None
+}
+
+/// Serves one client connection. `user` is who the operating system says is on the other end of
+/// the local socket; it replaces the user the client claims to be in the audit log.
+async fn handle_client(
+    connection: impl AsyncRead + AsyncWrite + Send + 'static,
+    context: Context,
+    peer: Option<String>,
+    user: Option<String>,
+) {
+    let (mut reader, mut writer) = tokio::io::split(connection);
+    let (tx, mut rx) = mpsc::unbounded_channel::<(u8, Vec<u8>)>();
+
+    let writer_task = tokio::spawn(async move {
+        while let Some((tag, payload)) = rx.recv().await {
+            if detached::write_frame(&mut writer, tag, &payload)
+                .await
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+
+    // `exec`, `status` and `stop --cancel` clients only want their own response, so the console
+    // stream (and its replay) is only subscribed to once a client sends anything else.
+    let mut registered = false;
+    let mut identity = None;
+    // Once a client has attached read-only it stays that way, even if it attaches again.
+    let mut read_only = false;
+
+    loop {
+        let frame = match detached::read_frame(&mut reader).await {
+            Ok(frame) => frame,
+            Err(_) => break,
+        };
+
+        if !registered
+            && !matches!(
+                frame.0,
+                detached::TAG_EXEC | detached::TAG_STATUS | detached::TAG_CANCEL
+            )
+        {
+            context.shared.lock().await.register(tx.clone());
+            registered = true;
+        }
+
+        match frame {
+            (detached::TAG_ATTACH, payload) => {
+                identity = verified(
+                    serde_json::from_slice::<Identity>(&payload).ok(),
+                    user.as_deref(),
+                );
+                read_only |= identity.as_ref().is_some_and(|identity| identity.read_only);
+            }
+            (
+                detached::TAG_STDIN
+                | detached::TAG_STOP
+                | detached::TAG_RESTART_SERVER
+                | detached::TAG_EXEC
+                | detached::TAG_CANCEL,
+                _,
+            ) if read_only => {}
+            (detached::TAG_STDIN, payload) => {
+                audit(identity.as_ref(), peer.as_deref(), &payload);
+
+                // Typing the stop command into the console is a deliberate stop, not a crash, so
+                // it must not trigger a restart either.
+                {
+                    let mut shared = context.shared.lock().await;
+                    if is_stop_command(&payload, &shared.spec.stop_command) {
+                        shared.stop_requested = true;
+                    }
+                }
+
+                context.console(payload).await;
+            }
+            (detached::TAG_STOP, payload) => {
+                // Older clients send just the timeout.
+                let request =
+                    serde_json::from_slice::<StopRequest>(&payload).unwrap_or_else(|_| {
+                        StopRequest {
+                            timeout: payload
+                                .as_slice()
+                                .try_into()
+                                .map(u64::from_be_bytes)
+                                .unwrap_or(20),
+                            countdown: 0,
+                            spec: None,
+                        }
+                    });
+
+                self::request(&context, request.countdown, Control::Stop(request.timeout)).await;
+            }
+            (detached::TAG_RESTART_SERVER, payload) => {
+                let Ok(request) = serde_json::from_slice::<StopRequest>(&payload) else {
+                    break;
+                };
+
+                self::request(
+                    &context,
+                    request.countdown,
+                    Control::Restart(request.timeout, request.spec.map(Box::new)),
+                )
+                .await;
+            }
+            (detached::TAG_CANCEL, _) => {
+                let cancelled = cancel_countdown(&context).await;
+                let _ = tx.send((detached::TAG_CANCEL_RESULT, vec![cancelled as u8]));
+            }
+            (detached::TAG_EXEC, payload) => {
+                let Ok(mut request) = serde_json::from_slice::<ExecRequest>(&payload) else {
+                    break;
+                };
+                request.identity = verified(request.identity, user.as_deref());
+
+                let output = exec(&context, request, peer.as_deref()).await;
+                let _ = tx.send((detached::TAG_EXEC_RESULT, output));
+            }
+            (detached::TAG_STATUS, _) => {
+                let (mut status, java_pid) = {
+                    let shared = context.shared.lock().await;
+                    (shared.status(), shared.java_pid)
+                };
+                status.process = process_status(java_pid);
+
+                let _ = tx.send((
+                    detached::TAG_STATUS_RESULT,
+                    serde_json::to_vec(&status).unwrap_or_default(),
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    writer_task.abort();
+}
+
+/// Records the resource usage of the current java process into the on-disk series.
+async fn sampler() {
+    let mut sampler = usage::Sampler::new();
+    let mut count = usage::read(i64::MIN).len();
+    let mut last_pid = None;
+
+    let mut interval = tokio::time::interval(Duration::from_secs(usage::SAMPLE_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+
+        let Some(state) = detached::read_state() else {
+            continue;
+        };
+
+        let sample = sampler.sample(state.java_pid);
+        // The first sample of a new process has no cpu or disk usage to compare against yet.
+        if last_pid.replace(state.java_pid) != Some(state.java_pid) {
+            continue;
+        }
+
+        if let Some(sample) = sample {
+            let _ = usage::append(&sample, &mut count);
+        }
+    }
+}
+
+/// Spawns the server, with its console input on a FIFO where possible (see `process::create_fifo`).
+fn spawn_server(
+    spec: &Spec,
+    cgroup: Option<&std::fs::File>,
+) -> std::io::Result<(Server, Option<Stdin>)> {
+    #[cfg(unix)]
+    if let Ok((reader, writer)) = process::create_fifo() {
+        let child = spawn_java(spec, Stdio::from(reader), cgroup)?;
+        let writer = Stdin::Writer(Box::new(tokio::fs::File::from_std(writer)));
+
+        return Ok((Server::Spawned(child), Some(writer)));
+    }
+
+    let mut child = spawn_java(spec, Stdio::piped(), cgroup)?;
+    let stdin = child
+        .stdin
+        .take()
+        .map(|stdin| Stdin::Writer(Box::new(stdin)));
+
+    Ok((Server::Spawned(child), stdin))
+}
+
+/// Takes over a server whose daemon died. Console input goes through its stdin FIFO if it has
+/// one, otherwise over RCON if that is enabled.
+fn adopt_server(pid: u32) -> (Server, Option<Stdin>, &'static str) {
+    if let Some(fifo) = process::open_fifo() {
+        return (
+            Server::Adopted { pid, console: true },
+            Some(Stdin::Writer(Box::new(tokio::fs::File::from_std(fifo)))),
+            "console input is reconnected",
+        );
+    }
+
+    if let (true, (address, Some(password))) = (rcon::enabled(), rcon::local()) {
+        return (
+            Server::Adopted { pid, console: true },
+            Some(Stdin::Rcon {
+                address,
+                password,
+                connection: None,
+            }),
+            "console commands are sent over rcon",
+        );
+    }
+
+    (
+        Server::Adopted {
+            pid,
+            console: false,
+        },
+        None,
+        "console input is unavailable, stopping sends SIGTERM",
+    )
+}
+
+pub async fn run(adopt: Option<u32>) -> Result<i32, anyhow::Error> {
+    let mut spec = detached::read_spec()?;
+
+    let identifier: String = rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(12)
+        .map(char::from)
+        .collect();
+
+    let directory = std::env::current_dir()?.to_string_lossy().to_string();
+    let profile = config::Config::new_optional(".mcvcli.json")
+        .map(|config| config.profile_name)
+        .unwrap_or_else(|| "default".to_string());
+
+    let (notifications_tx, notifications_rx) = mpsc::unbounded_channel();
+    let shared = Arc::new(Mutex::new(Shared::new(
+        LogWriter::new(
+            spec.log_max_bytes,
+            spec.log_retention,
+            spec.log_retention_days,
+        ),
+        spec.clone(),
+        notifications_tx,
+    )));
+    let dispatcher = tokio::spawn(dispatch(
+        Arc::clone(&shared),
+        profile.clone(),
+        notifications_rx,
+    ));
+
+    // The stdin channel outlives any single java process, so writes always go to the current one.
+    let child_stdin: Arc<Mutex<Option<Stdin>>> = Arc::new(Mutex::new(None));
+    let (stdin_tx, mut stdin_rx) = mpsc::unbounded_channel::<Vec<u8>>();
+    {
+        let child_stdin = Arc::clone(&child_stdin);
+        let shared = Arc::clone(&shared);
+        tokio::spawn(async move {
+            while let Some(bytes) = stdin_rx.recv().await {
+                if let Some(stdin) = child_stdin.lock().await.as_mut() {
+                    stdin.send(&bytes, &shared).await;
+                }
+            }
+        });
+    }
+
+    let (control_tx, mut control_rx) = mpsc::unbounded_channel::<Control>();
+    let context = Context {
+        shared: Arc::clone(&shared),
+        stdin_tx: stdin_tx.clone(),
+        control_tx,
+        exec_lock: Arc::new(Mutex::new(())),
+    };
+
+    let name = detached::socket_label(&identifier).to_ns_name::<GenericNamespaced>()?;
+    let listener = ListenerOptions::new().name(name).create_tokio()?;
+    {
+        let context = context.clone();
+        tokio::spawn(async move {
+            loop {
+                let connection = match listener.accept().await {
+                    Ok(connection) => connection,
+                    Err(_) => break,
+                };
+
+                let user = peer_user(&connection);
+                tokio::spawn(handle_client(connection, context.clone(), None, user));
+            }
+        });
+    }
+
+    tokio::spawn(scheduler(context.clone()));
+    tokio::spawn(watchdog::run(context.clone()));
+    tokio::spawn(sampler());
+
+    if let Some(address) = &spec.metrics_address {
+        match tokio::net::TcpListener::bind(address).await {
+            Ok(listener) => {
+                tokio::spawn(metrics::serve(
+                    listener,
+                    Arc::clone(&shared),
+                    profile.clone(),
+                ));
+            }
+            Err(err) => shared.lock().await.log.write(
+                format!("\n[mcvcli] warning: failed to serve metrics on {address}: {err}\n")
+                    .as_bytes(),
+            ),
+        }
+    }
+
+    if let Some(address) = &spec.remote_address {
+        let acceptor = spec.remote_tls.as_ref().map(Tls::acceptor).transpose();
+        let listener = match (&spec.remote_token, acceptor) {
+            (Some(token), Ok(acceptor)) => tokio::net::TcpListener::bind(address)
+                .await
+                .map(|listener| (listener, token.clone(), acceptor))
+                .map_err(|err| err.to_string()),
+            (None, _) => Err("no remote token is configured".to_string()),
+            (_, Err(err)) => Err(err.to_string()),
+        };
+
+        match listener {
+            Ok((listener, token, acceptor)) => {
+                tokio::spawn(remote::serve(listener, context.clone(), token, acceptor));
+            }
+            Err(err) => shared.lock().await.log.write(
+                format!(
+                    "\n[mcvcli] warning: failed to accept remote control on {address}: {err}\n"
+                )
+                .as_bytes(),
+            ),
+        }
+    }
+
+    // Looked up before the daemon possibly moves itself into a leaf, so every respawn creates the
+    // server's group in the same place.
+    let cgroup_parent = cgroup::parent();
+    let mut cgroup_group = None;
+    let mut attempt = 0;
+    let mut first = true;
+    let exit_code = loop {
+        // The spec is re-read for every respawn, so `mcvcli restart` can apply config changes.
+        if !first && let Ok(new) = detached::read_spec() {
+            spec = new;
+            shared.lock().await.spec = spec.clone();
+        }
+
+        // The first start ran its pre-start hook before the daemon was spawned.
+        let environment = hooks::Environment {
+            profile: profile.clone(),
+            jar_file: spec.jar_file.clone(),
+            java_version: spec.java_version,
+            ..Default::default()
+        };
+        if !first && !run_hook(&shared, Hook::PreStart, &environment).await {
+            shared
+                .lock()
+                .await
+                .log
+                .write(b"\n[mcvcli] not starting the server again, its pre-start hook failed\n");
+            break 1;
+        }
+
+        let adopting = adopt.filter(|_| first);
+
+        let mut limited = None;
+        if adopting.is_none() && !spec.limits.is_empty() {
+            match cgroup_parent
+                .as_deref()
+                .ok_or_else(|| anyhow::anyhow!("could not find own cgroup"))
+                .and_then(|parent| cgroup::setup(parent, &identifier, &spec.limits))
+                .and_then(|group| cgroup::procs(&group).map(|procs| (group, procs)))
+            {
+                Ok(group) => limited = Some(group),
+                Err(err) => shared.lock().await.log.write(
+                    format!("\n[mcvcli] warning: resource limits are not applied: {err}\n")
+                        .as_bytes(),
+                ),
+            }
+        }
+
+        let spawned = match adopting {
+            Some(pid) => {
+                let (server, stdin, console) = adopt_server(pid);
+                shared.lock().await.log.write(
+                    format!("\n[mcvcli] recovered orphaned server (pid {pid}), {console}\n")
+                        .as_bytes(),
+                );
+
+                Ok((server, stdin))
+            }
+            None => spawn_server(&spec, limited.as_ref().map(|(_, procs)| procs)),
+        };
+        let (mut server, stdin) = match spawned {
+            Ok(spawned) => spawned,
+            Err(err) if first => return Err(err.into()),
+            Err(err) => {
+                shared
+                    .lock()
+                    .await
+                    .log
+                    .write(format!("\n[mcvcli] failed to restart server: {err}\n").as_bytes());
+                break 1;
+            }
+        };
+        first = false;
+
+        let java_pid = server.id();
+        let started_at = SystemTime::now();
+        let environment = hooks::Environment {
+            pid: Some(java_pid),
+            ..environment
+        };
+        shared.lock().await.environment = environment.clone();
+
+        if let Server::Adopted { .. } = server {
+            cgroup_group = cgroup::of_process(java_pid);
+        } else if let Some((group, _)) = limited {
+            // An adopted server may have been in the group of the daemon that died.
+            if let Some(previous) = cgroup_group.replace(group.clone())
+                && previous != group
+            {
+                cgroup::remove(&previous);
+            }
+        }
+        detached::write_state(&State {
+            identifier: identifier.clone(),
+            daemon_pid: std::process::id(),
+            java_pid,
+        })?;
+
+        let _ = registry::register(&registry::Entry {
+            identifier: identifier.clone(),
+            directory: directory.clone(),
+            profile: profile.clone(),
+            daemon_pid: std::process::id(),
+            java_pid,
+            port: properties::server_port(),
+        });
+
+        *child_stdin.lock().await = stdin;
+        {
+            let mut shared = shared.lock().await;
+            shared.reset();
+            shared.java_pid = java_pid;
+
+            match server {
+                Server::Spawned(_) => {
+                    let (ready_tx, ready_rx) = oneshot::channel();
+                    shared.ready = Some(ready_tx);
+
+                    let post_start = Arc::clone(&context.shared);
+                    let environment = environment.clone();
+                    tokio::spawn(async move {
+                        if ready_rx.await.is_ok() {
+                            run_hook(&post_start, Hook::PostStart, &environment).await;
+                        }
+                    });
+                }
+                // Its `Done` line was printed to the daemon that died.
+                Server::Adopted { .. } => shared.ready_at = Some(chrono::Utc::now().timestamp()),
+            }
+
+            shared.notify(
+                Notification::new(
+                    webhooks::Event::Start,
+                    match server {
+                        Server::Spawned(_) => format!("server started (pid {java_pid})"),
+                        Server::Adopted { .. } => format!("server recovered (pid {java_pid})"),
+                    },
+                )
+                .field("pid", java_pid),
+            );
+        }
+
+        let mut tail = None;
+        match &mut server {
+            Server::Spawned(child) => {
+                if let Some(stdout) = child.stdout.take() {
+                    pipe_output(&shared, stdout, detached::TAG_STDOUT);
+                }
+                if let Some(stderr) = child.stderr.take() {
+                    pipe_output(&shared, stderr, detached::TAG_STDERR);
+                }
+            }
+            Server::Adopted { .. } => tail = Some(process::tail_server_log(&shared)),
+        }
+
+        let started = Instant::now();
+        let (status, exit) = supervise(
+            &mut server,
+            &mut control_rx,
+            &stdin_tx,
+            &spec.stop_command,
+            &shared,
+            &environment,
+        )
+        .await;
+        if let Some(tail) = tail {
+            tail.abort();
+        }
+        *child_stdin.lock().await = None;
+        let (code, success, crash) = {
+            let mut shared = shared.lock().await;
+            let (code, success) = match status {
+                Some(status) => (status.code().unwrap_or(0), status.success()),
+                None => adopted_outcome(&exit, shared.stop_requested, started_at),
+            };
+
+            shared.java_pid = 0;
+            shared.ready = None;
+            shared.ready_at = None;
+            shared.environment.pid = None;
+            *shared.exits.entry(code).or_default() += 1;
+
+            // Stops and restarts that ran into the timeout exit non-zero too, but aren't crashes.
+            let crash = (code != 0 && matches!(exit, Exit::Exited))
+                .then(|| crash::detect(started_at, &String::from_utf8_lossy(&shared.session)));
+            if let Some(summary) = &crash {
+                shared.report_crash(code, summary);
+            }
+
+            let _ = crash::record(&crash::Exit {
+                time: chrono::Utc::now().timestamp(),
+                code,
+                uptime_secs: started.elapsed().as_secs(),
+                crash: crash.clone(),
+            });
+
+            (code, success, crash)
+        };
+
+        let environment = hooks::Environment {
+            exit_code: Some(code),
+            exception: crash.as_ref().and_then(|summary| summary.exception.clone()),
+            ..environment
+        };
+        if crash.is_some() {
+            run_hook(&shared, Hook::OnCrash, &environment).await;
+        }
+        run_hook(&shared, Hook::PostStop, &environment).await;
+
+        match exit {
+            Exit::Stopped => break code,
+            Exit::Restarted => {
+                attempt = 0;
+                shared.lock().await.broadcast_restart(&RestartNotice {
+                    code,
+                    attempt: 0,
+                    max_retries: spec.restart_max_retries,
+                    delay_secs: 0,
+                    requested: true,
+                });
+                continue;
+            }
+            Exit::Exited => {}
+        }
+
+        if shared.lock().await.stop_requested || !spec.restart_policy.should_restart(success) {
+            break code;
+        }
+
+        if started.elapsed() >= STABLE_UPTIME {
+            attempt = 0;
+        }
+
+        if attempt >= spec.restart_max_retries {
+            shared.lock().await.log.write(
+                format!(
+                    "\n[mcvcli] server exited with code {code}, giving up after {attempt} restarts\n"
+                )
+                .as_bytes(),
+            );
+            break code;
+        }
+
+        attempt += 1;
+        let delay_secs = spec
+            .restart_backoff_secs
+            .saturating_mul(1 << (attempt - 1).min(16))
+            .min(MAX_BACKOFF_SECS);
+
+        shared.lock().await.broadcast_restart(&RestartNotice {
+            code,
+            attempt,
+            max_retries: spec.restart_max_retries,
+            delay_secs,
+            requested: false,
+        });
+
+        // A stop request during the backoff simply cancels the pending restart.
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(delay_secs)) => {}
+            Some(control) = control_rx.recv() => match control {
+                Control::Stop(_) => break code,
+                Control::Restart(_, new) => {
+                    apply_spec(&shared, new).await;
+                    attempt = 0;
+                }
+            },
+        }
+    };
+
+    {
+        let mut shared = shared.lock().await;
+        shared
+            .log
+            .write(format!("\n[mcvcli] server exited with code {exit_code}\n").as_bytes());
+        shared.broadcast_exit(exit_code);
+        shared.notify(
+            Notification::new(
+                webhooks::Event::Stop,
+                format!("server stopped with code {exit_code}"),
+            )
+            .field("code", exit_code),
+        );
+        shared.notifications = None;
+    }
+
+    tokio::time::sleep(Duration::from_millis(250)).await;
+
+    if let Some(group) = cgroup_group {
+        cgroup::remove(&group);
+    }
+    if let Some(parent) = &cgroup_parent {
+        cgroup::release(parent, &identifier);
+    }
+    // The server counts as stopped from here on, so `stop` and service managers don't wait for
+    // webhooks that are still being delivered (or retried), which get a few more seconds.
+    detached::cleanup();
+
+    let _ = tokio::time::timeout(WEBHOOK_DRAIN_TIMEOUT, dispatcher).await;
+
+    Ok(exit_code)
+}
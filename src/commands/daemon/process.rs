@@ -0,0 +1,259 @@
+use super::Shared;
+use crate::{detached, rcon::Rcon};
+
+use std::{process::ExitStatus, sync::Arc, time::Duration};
+use tokio::{
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWrite, AsyncWriteExt},
+    process::Child,
+    sync::Mutex,
+};
+
+/// The java process being supervised, either spawned by this daemon or adopted from one that
+/// died (see `mcvcli recover`).
+pub enum Server {
+    Spawned(Child),
+    /// Not our child, so there is no exit status to wait for. Without a `console` to send the
+    /// stop command to, the server is stopped with SIGTERM, which it handles like `stop`.
+    Adopted {
+        pid: u32,
+        console: bool,
+    },
+}
+
+impl Server {
+    pub fn id(&self) -> u32 {
+        match self {
+            Server::Spawned(child) => child.id().unwrap_or(0),
+            Server::Adopted { pid, .. } => *pid,
+        }
+    }
+
+    pub async fn wait(&mut self) -> Option<ExitStatus> {
+        match self {
+            Server::Spawned(child) => child.wait().await.ok(),
+            Server::Adopted { pid, .. } => {
+                while alive(*pid) {
+                    tokio::time::sleep(Duration::from_millis(500)).await;
+                }
+
+                None
+            }
+        }
+    }
+
+    /// Asks the server to stop when its console is out of reach, returns whether it was asked.
+    pub fn terminate(&self) -> bool {
+        match self {
+            Server::Adopted {
+                pid,
+                console: false,
+            } => signal(*pid, sysinfo::Signal::Term),
+            _ => false,
+        }
+    }
+
+    pub async fn kill(&mut self) -> Option<ExitStatus> {
+        match self {
+            Server::Spawned(child) => {
+                let _ = child.kill().await;
+                child.wait().await.ok()
+            }
+            Server::Adopted { pid, .. } => {
+                signal(*pid, sysinfo::Signal::Kill);
+                self.wait().await
+            }
+        }
+    }
+}
+
+fn alive(pid: u32) -> bool {
+    let pid = sysinfo::Pid::from(pid as usize);
+    let mut system = sysinfo::System::new();
+    system.refresh_processes_specifics(
+        sysinfo::ProcessesToUpdate::Some(&[pid]),
+        true,
+        sysinfo::ProcessRefreshKind::nothing(),
+    );
+
+    system
+        .process(pid)
+        .is_some_and(|process| process.status() != sysinfo::ProcessStatus::Zombie)
+}
+
+fn signal(pid: u32, signal: sysinfo::Signal) -> bool {
+    let pid = sysinfo::Pid::from(pid as usize);
+    let mut system = sysinfo::System::new();
+    system.refresh_processes_specifics(
+        sysinfo::ProcessesToUpdate::Some(&[pid]),
+        true,
+        sysinfo::ProcessRefreshKind::nothing(),
+    );
+
+    system
+        .process(pid)
+        .and_then(|process| process.kill_with(signal))
+        .unwrap_or(false)
+}
+
+/// Where console input goes.
+pub enum Stdin {
+    Writer(Box<dyn AsyncWrite + Send + Unpin>),
+    /// An adopted server without a stdin FIFO gets its commands over RCON instead, with the
+    /// replies shown as console output.
+    Rcon {
+        address: String,
+        password: String,
+        connection: Option<Rcon>,
+    },
+}
+
+impl Stdin {
+    pub async fn send(&mut self, bytes: &[u8], shared: &Arc<Mutex<Shared>>) {
+        match self {
+            Stdin::Writer(writer) => {
+                if writer.write_all(bytes).await.is_ok() {
+                    let _ = writer.flush().await;
+                }
+            }
+            Stdin::Rcon {
+                address,
+                password,
+                connection,
+            } => {
+                for command in String::from_utf8_lossy(bytes).lines() {
+                    if command.trim().is_empty() {
+                        continue;
+                    }
+
+                    // The connection is made lazily and dropped on errors, so a server that
+                    // restarted RCON (or wasn't listening yet) is picked up on the next command.
+                    if connection.is_none() {
+                        *connection = Rcon::connect(address, password, Duration::from_secs(5))
+                            .await
+                            .ok();
+                    }
+
+                    let reply = match connection.as_mut() {
+                        Some(rcon) => rcon.command(command.trim()).await,
+                        None => Err(anyhow::anyhow!("failed to connect to rcon on {address}")),
+                    };
+                    let output = match reply {
+                        Ok(reply) if reply.is_empty() => continue,
+                        Ok(reply) => format!("{}\n", reply.trim_end()),
+                        Err(err) => {
+                            *connection = None;
+                            format!("[mcvcli] failed to send command over rcon: {err}\n")
+                        }
+                    };
+
+                    shared
+                        .lock()
+                        .await
+                        .push(detached::TAG_STDOUT, output.into_bytes());
+                }
+            }
+        }
+    }
+}
+
+/// Creates the stdin FIFO and opens it for both reading and writing. The server gets its own
+/// handle, so it never sees the end of its input when the daemon dies, and a new daemon can
+/// open the FIFO again to take over the console.
+#[cfg(unix)]
+pub fn create_fifo() -> std::io::Result<(std::fs::File, std::fs::File)> {
+    use std::os::unix::{ffi::OsStrExt, fs::FileTypeExt};
+
+    let path = detached::stdin_path();
+    match std::fs::metadata(&path) {
+        Ok(metadata) if metadata.file_type().is_fifo() => {}
+        Ok(_) => std::fs::remove_file(&path)?,
+        Err(_) => {}
+    }
+
+    if !path.exists() {
+        let c_path = std::ffi::CString::new(path.as_os_str().as_bytes())?;
+        if unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+
+    let file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(&path)?;
+
+    Ok((file.try_clone()?, file))
+}
+
+/// Opens the stdin FIFO of an adopted server. This only works if something still has it open
+/// for reading, which after the old daemon is gone can only be the server.
+#[cfg(unix)]
+pub fn open_fifo() -> Option<std::fs::File> {
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let file = std::fs::OpenOptions::new()
+        .write(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(detached::stdin_path())
+        .ok()?;
+
+    // Writes should wait for the server to read, like they do with a pipe.
+    unsafe {
+        use std::os::fd::AsRawFd;
+
+        let flags = libc::fcntl(file.as_raw_fd(), libc::F_GETFL);
+        libc::fcntl(file.as_raw_fd(), libc::F_SETFL, flags & !libc::O_NONBLOCK);
+    }
+
+    Some(file)
+}
+
+#[cfg(not(unix))]
+pub fn open_fifo() -> Option<std::fs::File> {
+    None
+}
+
+/// The stdout pipe of an adopted server died with the old daemon, so its output is read from
+/// the log file the server writes itself, starting at the current end.
+pub fn tail_server_log(shared: &Arc<Mutex<Shared>>) -> tokio::task::AbortHandle {
+    let shared = Arc::clone(shared);
+
+    tokio::spawn(async move {
+        let path = std::path::Path::new("logs").join("latest.log");
+        let mut position = tokio::fs::metadata(&path)
+            .await
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+        let mut buffer = vec![0u8; 8192];
+
+        loop {
+            tokio::time::sleep(Duration::from_millis(250)).await;
+
+            let Ok(mut file) = tokio::fs::File::open(&path).await else {
+                continue;
+            };
+            let Ok(metadata) = file.metadata().await else {
+                continue;
+            };
+
+            // The server starts a new file (and archives the old one) when it restarts.
+            if metadata.len() < position {
+                position = 0;
+            }
+            if metadata.len() == position
+                || file.seek(std::io::SeekFrom::Start(position)).await.is_err()
+            {
+                continue;
+            }
+
+            while let Ok(n @ 1..) = file.read(&mut buffer).await {
+                position += n as u64;
+                shared
+                    .lock()
+                    .await
+                    .push(detached::TAG_STDOUT, buffer[..n].to_vec());
+            }
+        }
+    })
+    .abort_handle()
+}
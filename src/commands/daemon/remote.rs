@@ -0,0 +1,81 @@
+use super::{Context, handle_client};
+use crate::detached;
+
+use rand::RngExt;
+use std::{sync::Arc, time::Duration};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::TcpListener,
+};
+use tokio_rustls::TlsAcceptor;
+
+/// How long a remote client has to set up TLS, and then to answer the challenge.
+const AUTH_TIMEOUT: Duration = Duration::from_secs(10);
+
+async fn authenticate(
+    stream: &mut (impl AsyncRead + AsyncWrite + Unpin),
+    token: &str,
+) -> Result<bool, anyhow::Error> {
+    let challenge: [u8; 32] = rand::rng().random();
+    detached::write_frame(stream, detached::TAG_CHALLENGE, &challenge).await?;
+
+    // Nothing but the fixed size response is read from a client that hasn't authenticated yet.
+    let (tag, response) = detached::read_frame_limited(stream, detached::AUTH_RESPONSE_LEN).await?;
+    let authenticated = tag == detached::TAG_AUTH
+        && detached::constant_time_eq(&response, &detached::auth_response(&challenge, token));
+
+    detached::write_frame(stream, detached::TAG_AUTH_RESULT, &[authenticated as u8]).await?;
+
+    Ok(authenticated)
+}
+
+async fn session(
+    mut stream: impl AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    context: Context,
+    token: &str,
+    peer: String,
+) {
+    match tokio::time::timeout(AUTH_TIMEOUT, authenticate(&mut stream, token)).await {
+        Ok(Ok(true)) => handle_client(stream, context, Some(peer), None).await,
+        Ok(Ok(false)) => {
+            context.shared.lock().await.log.write(
+                format!("\n[mcvcli] rejected remote client {peer}: wrong token\n").as_bytes(),
+            )
+        }
+        _ => {}
+    }
+}
+
+/// Accepts remote control connections, which speak the same protocol as the local socket once
+/// they have authenticated (see `detached::Remote`). With `acceptor`, clients must first set up
+/// mutual TLS with a certificate signed by the configured CA.
+pub async fn serve(
+    listener: TcpListener,
+    context: Context,
+    token: String,
+    acceptor: Option<TlsAcceptor>,
+) {
+    let token = Arc::new(token);
+
+    while let Ok((stream, peer)) = listener.accept().await {
+        let context = context.clone();
+        let token = Arc::clone(&token);
+        let acceptor = acceptor.clone();
+
+        tokio::spawn(async move {
+            let Some(acceptor) = acceptor else {
+                return session(stream, context, &token, peer.to_string()).await;
+            };
+
+            match tokio::time::timeout(AUTH_TIMEOUT, acceptor.accept(stream)).await {
+                Ok(Ok(stream)) => session(stream, context, &token, peer.to_string()).await,
+                Ok(Err(err)) => {
+                    context.shared.lock().await.log.write(
+                        format!("\n[mcvcli] rejected remote client {peer}: {err}\n").as_bytes(),
+                    )
+                }
+                Err(_) => {}
+            }
+        });
+    }
+}
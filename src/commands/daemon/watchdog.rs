@@ -0,0 +1,133 @@
+use super::{Context, Control, request};
+use crate::{detached, ping};
+
+use std::time::{Duration, Instant};
+#[cfg(unix)]
+use tokio::sync::mpsc;
+
+/// How long a frozen server gets to act on the stop command before it is killed.
+const STOP_TIMEOUT_SECS: u64 = 20;
+const PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Asks the JVM for a thread dump, with `jcmd` from the server's java home or otherwise SIGQUIT.
+async fn thread_dump(context: &Context, pid: u32, java_home: &str) -> Option<Vec<u8>> {
+    let jcmd = std::path::Path::new(java_home).join("bin").join("jcmd");
+    let output = tokio::time::timeout(
+        Duration::from_secs(10),
+        tokio::process::Command::new(jcmd)
+            .arg(pid.to_string())
+            .arg("Thread.print")
+            .output(),
+    )
+    .await;
+
+    match output {
+        Ok(Ok(output)) if output.status.success() => Some(output.stdout),
+        _ => sigquit_dump(context, pid).await,
+    }
+}
+
+/// SIGQUIT makes the JVM print a thread dump to its stdout, which is collected like `exec`
+/// output.
+#[cfg(unix)]
+async fn sigquit_dump(context: &Context, pid: u32) -> Option<Vec<u8>> {
+    let (tx, mut rx) = mpsc::unbounded_channel::<Vec<u8>>();
+    context.shared.lock().await.captures.push(tx);
+
+    if unsafe { libc::kill(pid as i32, libc::SIGQUIT) } != 0 {
+        return None;
+    }
+
+    let mut dump = Vec::new();
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(3);
+    while let Ok(Some(data)) = tokio::time::timeout_at(deadline, rx.recv()).await {
+        dump.extend_from_slice(&data);
+    }
+
+    Some(dump).filter(|dump| !dump.is_empty())
+}
+
+#[cfg(not(unix))]
+async fn sigquit_dump(_context: &Context, _pid: u32) -> Option<Vec<u8>> {
+    None
+}
+
+/// Pings the server on its `server-port` and restarts it once it failed to answer `failures`
+/// times in a row, after saving a thread dump next to the console log. A booting server is left
+/// alone for the grace period, or until it printed its `Done` line.
+pub async fn run(context: Context) {
+    let mut failures = 0;
+    let mut current = (0, Instant::now());
+
+    loop {
+        let settings = context.shared.lock().await.spec.watchdog.clone();
+        let Some(settings) = settings else {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            continue;
+        };
+
+        tokio::time::sleep(Duration::from_secs(settings.interval_secs)).await;
+
+        let (pid, ready, stopping, java_home) = {
+            let shared = context.shared.lock().await;
+            (
+                shared.java_pid,
+                shared.ready_at.is_some(),
+                shared.stop_requested || shared.countdown.is_some(),
+                shared.spec.java_home.clone(),
+            )
+        };
+
+        if pid != current.0 {
+            current = (pid, Instant::now());
+            failures = 0;
+        }
+        if pid == 0
+            || stopping
+            || (!ready && current.1.elapsed() < Duration::from_secs(settings.grace_secs))
+        {
+            continue;
+        }
+
+        if ping::ping_local(PING_TIMEOUT).await {
+            failures = 0;
+            continue;
+        }
+
+        failures += 1;
+        context.shared.lock().await.log.write(
+            format!(
+                "\n[mcvcli] watchdog: server did not answer a ping ({failures}/{})\n",
+                settings.failures
+            )
+            .as_bytes(),
+        );
+
+        if failures < settings.failures {
+            continue;
+        }
+        failures = 0;
+
+        let path = detached::dir().join(format!(
+            "threaddump-{}.txt",
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        ));
+        let saved = match thread_dump(&context, pid, &java_home).await {
+            Some(dump) => std::fs::write(&path, dump).is_ok(),
+            None => false,
+        };
+
+        context.shared.lock().await.log.write(
+            match saved {
+                true => format!(
+                    "\n[mcvcli] watchdog: server is not responding, saved a thread dump to {} and restarting it\n",
+                    path.display()
+                ),
+                false => "\n[mcvcli] watchdog: server is not responding, restarting it (no thread dump could be taken)\n".to_string(),
+            }
+            .as_bytes(),
+        );
+
+        request(&context, 0, Control::Restart(STOP_TIMEOUT_SECS, None)).await;
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{detached, remotes};
+
+use clap::ArgMatches;
+use colored::Colorize;
+
+/// Console output that vanilla, Spigot/Paper and Fabric print for commands they don't know.
+const UNKNOWN_COMMAND: &[&str] = &["Unknown or incomplete command", "Unknown command"];
+
+pub async fn exec(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
+    let command = matches.get_one::<String>("command").expect("required");
+    let quiet = *matches.get_one::<u64>("quiet").expect("required");
+    let timeout = *matches.get_one::<u64>("timeout").expect("required");
+    let remote = match remotes::resolve(matches) {
+        Ok(remote) => remote,
+        Err(err) => {
+            eprintln!("{}", err.to_string().red());
+            return Ok(1);
+        }
+    };
+
+    if remote.is_none() && !detached::is_running() {
+        eprintln!(
+            "{} {}",
+            "server is not running, use".red(),
+            "mcvcli start --detached".cyan()
+        );
+        return Ok(1);
+    }
+
+    let connection = match detached::connect_to(remote.as_ref()).await {
+        Ok(connection) => connection,
+        Err(err) if remote.is_some() => {
+            eprintln!("{} {}", "failed to connect to remote:".red(), err);
+            return Ok(1);
+        }
+        Err(_) => {
+            eprintln!(
+                "{} {}",
+                "the server is orphaned (its supervisor is gone), use".red(),
+                "mcvcli recover".cyan()
+            );
+            return Ok(1);
+        }
+    };
+    let (mut reader, mut writer) = tokio::io::split(connection);
+
+    let request = detached::ExecRequest {
+        command: command.to_string(),
+        quiet_ms: quiet,
+        timeout_ms: timeout.saturating_mul(1000),
+        identity: Some(detached::Identity::current(None, false)),
+        until: Vec::new(),
+    };
+    detached::write_frame(
+        &mut writer,
+        detached::TAG_EXEC,
+        &serde_json::to_vec(&request)?,
+    )
+    .await?;
+
+    let output = loop {
+        match detached::read_frame(&mut reader).await {
+            Ok((detached::TAG_EXEC_RESULT, data)) => break data,
+            Ok(_) => {}
+            Err(_) => {
+                eprintln!("{}", "disconnected from server".red());
+                return Ok(1);
+            }
+        }
+    };
+
+    // The daemon waits the full timeout for the first output, so none means it timed out.
+    if output.is_empty() {
+        eprintln!(
+            "{} {}",
+            "the server printed nothing within".red(),
+            format!("{timeout}s").cyan()
+        );
+        return Ok(3);
+    }
+
+    let output = String::from_utf8_lossy(&output);
+    for line in output.lines() {
+        println!("{line}");
+    }
+
+    if output
+        .lines()
+        .any(|line| UNKNOWN_COMMAND.iter().any(|unknown| line.contains(unknown)))
+    {
+        return Ok(2);
+    }
+
+    Ok(0)
+}
@@ -0,0 +1,324 @@
+use crate::{
+    config, detached,
+    logs::{self, Level},
+};
+
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
+use clap::ArgMatches;
+use colored::Colorize;
+use interprocess::local_socket::tokio::Stream;
+use regex::Regex;
+use std::{
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+struct Filter {
+    since: Option<NaiveDateTime>,
+    level: Option<Level>,
+    grep: Option<Regex>,
+}
+
+impl Filter {
+    fn matches(&self, line: &str, time: Option<NaiveDateTime>, level: Option<Level>) -> bool {
+        if let Some(since) = self.since
+            && time.is_none_or(|time| time < since)
+        {
+            return false;
+        }
+
+        if let Some(minimum) = self.level
+            && level.is_none_or(|level| level < minimum)
+        {
+            return false;
+        }
+
+        self.grep.as_ref().is_none_or(|grep| grep.is_match(line))
+    }
+}
+
+/// Log lines only carry a time of day, so dates are reconstructed by counting the midnight
+/// rollovers. Lines without a header (stack traces) inherit the time and level of the last one.
+struct Dater {
+    date: NaiveDate,
+    previous: Option<NaiveTime>,
+    level: Option<Level>,
+}
+
+fn is_rollover(previous: Option<NaiveTime>, time: NaiveTime) -> bool {
+    previous.is_some_and(|previous| previous.signed_duration_since(time) > TimeDelta::hours(12))
+}
+
+impl Dater {
+    fn new(date: NaiveDate) -> Self {
+        Dater {
+            date,
+            previous: None,
+            level: None,
+        }
+    }
+
+    /// Starts at the date that makes the last line of `content` fall on `end`.
+    fn ending_at(content: &str, end: NaiveDate) -> Self {
+        let mut previous = None;
+        let mut rollovers = 0;
+        for entry in content.lines().filter_map(logs::parse) {
+            if is_rollover(previous, entry.time) {
+                rollovers += 1;
+            }
+            previous = Some(entry.time);
+        }
+
+        Dater::new(end - TimeDelta::days(rollovers))
+    }
+
+    fn next(&mut self, line: &str) -> (Option<NaiveDateTime>, Option<Level>) {
+        if let Some(entry) = logs::parse(line) {
+            if is_rollover(self.previous, entry.time) {
+                self.date = self.date.succ_opt().unwrap_or(self.date);
+            }
+
+            self.previous = Some(entry.time);
+            self.level = entry.level;
+        }
+
+        (
+            self.previous.map(|time| self.date.and_time(time)),
+            self.level,
+        )
+    }
+}
+
+fn parse_since(since: &str) -> Option<NaiveDateTime> {
+    let now = Local::now().naive_local();
+
+    if since.ends_with(|c: char| c.is_ascii_alphabetic()) {
+        return now.checked_sub_signed(logs::parse_duration(since)?);
+    }
+
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S"] {
+        if let Ok(time) = NaiveDateTime::parse_from_str(since, format) {
+            return Some(time);
+        }
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(since, "%Y-%m-%d") {
+        return Some(date.and_time(NaiveTime::MIN));
+    }
+
+    for format in ["%H:%M:%S", "%H:%M"] {
+        if let Ok(time) = NaiveTime::parse_from_str(since, format) {
+            return Some(now.date().and_time(time));
+        }
+    }
+
+    None
+}
+
+fn next_line(pending: &mut Vec<u8>) -> Option<String> {
+    let position = pending.iter().position(|byte| *byte == b'\n')?;
+    let line: Vec<u8> = pending.drain(..=position).collect();
+
+    Some(String::from_utf8_lossy(&line).trim_end().to_string())
+}
+
+fn print_tail(lines: Vec<String>, tail: Option<usize>) {
+    let skip = tail.map_or(0, |tail| lines.len().saturating_sub(tail));
+
+    for line in lines.into_iter().skip(skip) {
+        println!("{line}");
+    }
+}
+
+/// Follows the console through the daemon: the ring buffer it replays on connect provides the
+/// backlog, after which lines are printed as the server writes them.
+async fn follow_daemon(
+    connection: Stream,
+    tail: Option<usize>,
+    filter: &Filter,
+) -> Result<i32, anyhow::Error> {
+    let (mut reader, mut writer) = tokio::io::split(connection);
+
+    detached::write_frame(&mut writer, detached::TAG_FOLLOW, &[]).await?;
+
+    let mut pending = Vec::new();
+    loop {
+        match detached::read_frame(&mut reader).await? {
+            (detached::TAG_STDOUT | detached::TAG_STDERR, data) => pending.extend(data),
+            (detached::TAG_REPLAY_END, _) => break,
+            _ => {}
+        }
+    }
+
+    let mut dater = Dater::new(Local::now().date_naive());
+    let mut backlog = Vec::new();
+    while let Some(line) = next_line(&mut pending) {
+        let (time, level) = dater.next(&line);
+
+        if filter.matches(&line, time, level) {
+            backlog.push(line);
+        }
+    }
+    print_tail(backlog, tail);
+
+    loop {
+        match detached::read_frame(&mut reader).await {
+            Ok((detached::TAG_STDOUT | detached::TAG_STDERR, data)) => {
+                pending.extend(data);
+
+                while let Some(line) = next_line(&mut pending) {
+                    let (time, level) = dater.next(&line);
+
+                    if filter.matches(&line, time, level) {
+                        println!("{line}");
+                    }
+                }
+            }
+            Ok((detached::TAG_EXIT, data)) => {
+                let code = data
+                    .as_slice()
+                    .try_into()
+                    .map(i32::from_be_bytes)
+                    .unwrap_or(0);
+
+                println!();
+                println!("{} {}", "server has stopped with code".red(), code);
+                return Ok(0);
+            }
+            Ok(_) => {}
+            Err(_) => {
+                println!();
+                println!("{}", "disconnected from server".red());
+                return Ok(0);
+            }
+        }
+    }
+}
+
+/// Follows a log file by polling it for new data, starting over when it gets rotated.
+async fn follow_file(path: &Path, filter: &Filter) -> Result<i32, anyhow::Error> {
+    let mut position = path.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+    let mut dater = Dater::new(Local::now().date_naive());
+    let mut pending = Vec::new();
+
+    loop {
+        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+
+        let Ok(mut file) = std::fs::File::open(path) else {
+            continue;
+        };
+        let length = file.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+
+        if length < position {
+            position = 0;
+            pending.clear();
+        }
+        if length == position {
+            continue;
+        }
+
+        file.seek(SeekFrom::Start(position))?;
+        let read = file.read_to_end(&mut pending)?;
+        position += read as u64;
+
+        while let Some(line) = next_line(&mut pending) {
+            let (time, level) = dater.next(&line);
+
+            if filter.matches(&line, time, level) {
+                println!("{line}");
+            }
+        }
+    }
+}
+
+pub async fn logs(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
+    let tail = matches.get_one::<usize>("tail").copied();
+    let follow = *matches.get_one::<bool>("follow").expect("required");
+    let history = *matches.get_one::<bool>("history").expect("required");
+    let since = matches.get_one::<String>("since");
+    let level = matches.get_one::<String>("level");
+    let grep = matches.get_one::<String>("grep");
+    let _config = config::Config::new(".mcvcli.json", false);
+
+    let since = match since {
+        Some(since) => match parse_since(since) {
+            Some(since) => Some(since),
+            None => {
+                println!(
+                    "{} {} {}",
+                    "invalid time".red(),
+                    since.cyan(),
+                    "(use e.g. 30m, 2h, 1d, 14:30 or 2024-01-31 14:30)".red()
+                );
+                return Ok(1);
+            }
+        },
+        None => None,
+    };
+
+    let grep = match grep.map(|grep| Regex::new(grep)) {
+        Some(Ok(grep)) => Some(grep),
+        Some(Err(err)) => {
+            println!("{} {}", "invalid grep pattern:".red(), err);
+            return Ok(1);
+        }
+        None => None,
+    };
+
+    let filter = Filter {
+        since,
+        level: level.and_then(|level| Level::parse(level)),
+        grep,
+    };
+
+    if follow
+        && !history
+        && detached::is_running()
+        && let Ok(connection) = detached::connect().await
+    {
+        return follow_daemon(connection, tail, &filter).await;
+    }
+
+    let files: Vec<PathBuf> = if history {
+        logs::server_files()
+    } else {
+        let mut files = detached::log_archives();
+        if detached::log_path().exists() {
+            files.push(detached::log_path());
+        }
+
+        files
+    };
+
+    if files.is_empty() && !follow {
+        println!("{}", "no logs found".red());
+        return Ok(1);
+    }
+
+    let mut lines = Vec::new();
+    for file in files {
+        let content = logs::read(&file)?;
+        let mut dater = Dater::ending_at(&content, logs::end_date(&file));
+
+        for line in content.lines() {
+            let (time, level) = dater.next(line);
+
+            if filter.matches(line, time, level) {
+                lines.push(line.to_string());
+            }
+        }
+    }
+    print_tail(lines, tail);
+
+    if !follow {
+        return Ok(0);
+    }
+
+    let path = if history {
+        Path::new("logs").join("latest.log")
+    } else {
+        detached::log_path()
+    };
+
+    follow_file(&path, &filter).await
+}
@@ -1,17 +1,29 @@
 pub mod attach;
 pub mod backups;
 pub mod config;
+pub mod crash;
 pub mod daemon;
+pub mod exec;
 pub mod init;
 pub mod install;
 pub mod java;
+pub mod logs;
 pub mod lookup;
 pub mod mods;
 pub mod profile;
+pub mod ps;
 pub mod query;
+pub mod rcon;
+pub mod recover;
+pub mod remote;
+pub mod restart;
+pub mod schedule;
+pub mod serve;
+pub mod service;
 pub mod start;
 pub mod status;
 pub mod stop;
 pub mod update;
 pub mod upgrade;
 pub mod version;
+pub mod webhook;
@@ -3,7 +3,6 @@ use crate::{config, detached, profiles};
 use clap::ArgMatches;
 use colored::Colorize;
 use dialoguer::{FuzzySelect, theme::ColorfulTheme};
-use std::path::Path;
 
 pub async fn r#use(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
     let name = matches.get_one::<String>("name");
@@ -66,39 +65,7 @@ pub async fn r#use(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
         "...".bright_black()
     );
 
-    let new_directory = format!(".mcvcli.profiles/{}", config.profile_name);
-    let old_directory = format!(".mcvcli.profiles/{name}");
-
-    if !Path::new(&new_directory).exists() {
-        std::fs::create_dir_all(&new_directory)?;
-    }
-
-    for entry in std::fs::read_dir(".")?.flatten() {
-        let path = entry.path();
-
-        let Some(file_name) = path.file_name() else {
-            continue;
-        };
-
-        if file_name == ".mcvcli.profiles" {
-            continue;
-        }
-
-        std::fs::rename(
-            &path,
-            format!("{}/{}", new_directory, file_name.to_string_lossy()),
-        )?;
-    }
-
-    for entry in std::fs::read_dir(&old_directory)?.flatten() {
-        let path = entry.path();
-
-        let Some(file_name) = path.file_name() else {
-            continue;
-        };
-
-        std::fs::rename(&path, format!("./{}", file_name.to_string_lossy()))?;
-    }
+    profiles::switch(&config.profile_name, name)?;
 
     println!(
         "{} {} {} {}",
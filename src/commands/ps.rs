@@ -0,0 +1,76 @@
+use crate::registry;
+
+use chrono::DateTime;
+use clap::ArgMatches;
+use colored::Colorize;
+use human_bytes::human_bytes;
+
+pub async fn ps(_matches: &ArgMatches) -> Result<i32, anyhow::Error> {
+    println!("{}", "listing running servers ...".bright_black());
+
+    let entries = registry::list();
+    let sys = sysinfo::System::new_all();
+
+    println!(
+        "{} {}",
+        "listing running servers ...".bright_black(),
+        "DONE".green().bold()
+    );
+
+    if entries.is_empty() {
+        println!();
+        println!("{}", "no servers are running".red());
+        return Ok(1);
+    }
+
+    for entry in entries {
+        println!();
+        println!(
+            "{} ({})",
+            entry.directory.cyan().bold().underline(),
+            entry.profile
+        );
+
+        println!(
+            "  {} {} (daemon {})",
+            "pid:         ".bright_black(),
+            entry.java_pid.to_string().cyan(),
+            entry.daemon_pid.to_string().cyan()
+        );
+        println!(
+            "  {} {}",
+            "port:        ".bright_black(),
+            entry.port.to_string().cyan()
+        );
+
+        let Some(process) = sys.process(sysinfo::Pid::from(entry.java_pid as usize)) else {
+            println!(
+                "  {} {}",
+                "status:      ".bright_black(),
+                "orphaned (java is gone)".red()
+            );
+            continue;
+        };
+
+        println!(
+            "  {} {}",
+            "memory usage:".bright_black(),
+            human_bytes(process.memory() as f64).cyan()
+        );
+
+        let uptime = chrono::Utc::now().timestamp() - process.start_time() as i64;
+        println!(
+            "  {} {} ({}h {}m {}s)",
+            "start time:  ".bright_black(),
+            DateTime::from_timestamp(process.start_time() as i64, 0)
+                .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_default()
+                .cyan(),
+            (uptime / 3600).to_string().cyan(),
+            ((uptime % 3600) / 60).to_string().cyan(),
+            (uptime % 60).to_string().cyan()
+        );
+    }
+
+    Ok(0)
+}
@@ -0,0 +1,142 @@
+use crate::{
+    console::{Console, Input},
+    detached,
+    rcon::{self, Rcon},
+};
+
+use clap::ArgMatches;
+use colored::Colorize;
+use std::{path::Path, time::Duration};
+use tokio::io::AsyncBufReadExt;
+
+/// The players from the output of `list`, e.g. `There are 1 of a max of 20 players online: Steve`.
+fn players(list: &str) -> Vec<String> {
+    list.split_once(':')
+        .map(|(_, players)| {
+            players
+                .split(',')
+                .map(|player| player.trim().to_string())
+                .filter(|player| !player.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub async fn rcon(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
+    let command = matches.get_one::<String>("command");
+    let timeout = *matches.get_one::<u64>("timeout").expect("required");
+
+    let (local_address, local_password) = rcon::local();
+    let local = matches.get_one::<String>("address").is_none();
+    let address = matches
+        .get_one::<String>("address")
+        .map(|address| {
+            if address.contains(':') {
+                address.clone()
+            } else {
+                format!("{address}:25575")
+            }
+        })
+        .unwrap_or(local_address);
+
+    let Some(password) = matches
+        .get_one::<String>("password")
+        .cloned()
+        .or(local_password)
+    else {
+        eprintln!(
+            "{} {} {}",
+            "no rcon password, use".red(),
+            "--password".cyan(),
+            "or set rcon.password in server.properties".red()
+        );
+        return Ok(1);
+    };
+
+    let mut connection =
+        match Rcon::connect(&address, &password, Duration::from_secs(timeout)).await {
+            Ok(connection) => connection,
+            Err(err) => {
+                eprintln!(
+                    "{} {}{} {}",
+                    "failed to connect to".red(),
+                    address.cyan(),
+                    ":".red(),
+                    err
+                );
+                if local && Path::new("server.properties").exists() && !rcon::enabled() {
+                    eprintln!(
+                        "{} {} {}",
+                        "rcon is disabled, set".red(),
+                        "enable-rcon=true".cyan(),
+                        "in server.properties".red()
+                    );
+                }
+                return Ok(1);
+            }
+        };
+
+    if let Some(command) = command {
+        let output = connection.command(command).await?;
+        for line in output.lines() {
+            println!("{line}");
+        }
+
+        return Ok(0);
+    }
+
+    // The history is shared with `attach` when this is the server in the current directory.
+    let history = (local && Path::new("server.properties").exists()).then(detached::history_path);
+
+    match Console::start(history) {
+        Some((console, mut input)) => {
+            println!("{} {}", "connected to".bright_black(), address.cyan());
+            println!(
+                "{}",
+                "(press ctrl-c or ctrl-d to disconnect)".bright_black()
+            );
+            println!();
+
+            if let Ok(list) = connection.command("list").await {
+                console.set_players(players(&list));
+            }
+
+            while let Some(Input::Line(line)) = input.recv().await {
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                match connection.command(line.trim()).await {
+                    Ok(output) => {
+                        if line.trim() == "list" {
+                            console.set_players(players(&output));
+                        }
+
+                        console.notice(output.trim_end());
+                    }
+                    Err(err) => {
+                        drop(console);
+                        eprintln!("{} {}", "disconnected from server:".red(), err);
+                        return Ok(1);
+                    }
+                }
+            }
+        }
+        None => {
+            let mut lines = tokio::io::BufReader::new(tokio::io::stdin()).lines();
+
+            while let Some(line) = lines.next_line().await? {
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                let output = connection.command(line.trim()).await?;
+                for line in output.lines() {
+                    println!("{line}");
+                }
+            }
+        }
+    }
+
+    Ok(0)
+}
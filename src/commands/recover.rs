@@ -0,0 +1,111 @@
+use crate::{config, detached, java};
+
+use clap::ArgMatches;
+use colored::Colorize;
+
+/// Starts a new daemon that takes over the orphaned server with the given pid, then waits for it
+/// to accept connections.
+pub async fn adopt(pid: u32) -> Result<bool, anyhow::Error> {
+    // The spec of the daemon that died is normally still there, it is only rebuilt from the
+    // config if something cleaned it up.
+    if detached::read_spec().is_err() {
+        let config = config::Config::new(".mcvcli.json", false);
+        let [binary, java_home] = java::binary(config.java_version).await?;
+        detached::write_spec(&detached::Spec::new(&config, binary, java_home))?;
+    }
+
+    detached::spawn_daemon(Some(pid))?;
+
+    for _ in 0..50 {
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+        if detached::orphan().is_none() && detached::connect().await.is_ok() {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// Recovers the orphaned server and reports it, returns whether it worked.
+pub async fn recover_orphan(pid: u32) -> Result<bool, anyhow::Error> {
+    println!(
+        "{} {}{}",
+        "recovering orphaned server (pid".bright_black(),
+        pid.to_string().cyan(),
+        ") ...".bright_black()
+    );
+
+    if !adopt(pid).await? {
+        println!(
+            "{} {}",
+            "failed to recover the server, check".red(),
+            ".mcvcli.detached/latest.log".cyan()
+        );
+        return Ok(false);
+    }
+
+    println!(
+        "{} {}{} {}",
+        "recovering orphaned server (pid".bright_black(),
+        pid.to_string().cyan(),
+        ") ...".bright_black(),
+        "DONE".green().bold()
+    );
+
+    Ok(true)
+}
+
+pub async fn recover(_matches: &ArgMatches) -> Result<i32, anyhow::Error> {
+    let Some(state) = detached::read_state() else {
+        println!(
+            "{} {}",
+            "server is not running, use".red(),
+            "mcvcli start --detached".cyan()
+        );
+        return Ok(1);
+    };
+
+    let sys = sysinfo::System::new_all();
+    if detached::process_alive(&sys, state.daemon_pid, "mcvcli") {
+        println!(
+            "{}",
+            "the server is not orphaned, its supervisor is still running".red()
+        );
+        return Ok(1);
+    }
+
+    let Some(pid) = detached::orphan() else {
+        println!(
+            "{}",
+            format!(
+                "the server (pid {}) is gone or belongs to another directory",
+                state.java_pid
+            )
+            .red()
+        );
+        println!("{}", "cleaning up stale state ...".bright_black());
+        detached::cleanup();
+        println!(
+            "{} {}",
+            "cleaning up stale state ...".bright_black(),
+            "DONE".green().bold()
+        );
+        return Ok(1);
+    };
+
+    if !recover_orphan(pid).await? {
+        return Ok(1);
+    }
+
+    println!();
+    println!(
+        "{} {} {} {}",
+        "use".bright_black(),
+        "mcvcli attach".cyan(),
+        "to open the console or".bright_black(),
+        "mcvcli stop".cyan()
+    );
+
+    Ok(0)
+}
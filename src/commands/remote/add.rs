@@ -0,0 +1,68 @@
+use crate::{detached::Remote, remotes, tls::Tls};
+
+use clap::ArgMatches;
+use colored::Colorize;
+
+pub async fn add(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
+    let name = matches.get_one::<String>("name").expect("required");
+    let address = matches.get_one::<String>("address").expect("required");
+    let token = matches.get_one::<String>("token").expect("required");
+    let tls_cert = matches.get_one::<String>("tls_cert");
+    let tls_key = matches.get_one::<String>("tls_key");
+    let tls_ca = matches.get_one::<String>("tls_ca");
+
+    if !address.contains(':') {
+        println!(
+            "{} {} {}",
+            "invalid address".red(),
+            address.cyan(),
+            "(use host:port)".red()
+        );
+        return Ok(1);
+    }
+
+    // The remote is used from any directory, so the files are saved with absolute paths.
+    let tls = match (tls_cert, tls_key, tls_ca) {
+        (Some(certificate), Some(key), Some(ca)) => {
+            let absolute = |path: &String| {
+                std::path::absolute(path).map(|path| path.to_string_lossy().to_string())
+            };
+
+            Some(Tls {
+                certificate: absolute(certificate)?,
+                key: absolute(key)?,
+                ca: absolute(ca)?,
+            })
+        }
+        _ => None,
+    };
+
+    let mut remotes = remotes::read();
+
+    println!(
+        "{} {} {}",
+        "saving remote".bright_black(),
+        name.cyan(),
+        "...".bright_black()
+    );
+
+    remotes.insert(
+        name.clone(),
+        Remote {
+            address: address.clone(),
+            token: token.clone(),
+            tls,
+        },
+    );
+    remotes::write(&remotes)?;
+
+    println!(
+        "{} {} {} {}",
+        "saving remote".bright_black(),
+        name.cyan(),
+        "...".bright_black(),
+        "DONE".green().bold()
+    );
+
+    Ok(0)
+}
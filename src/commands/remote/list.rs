@@ -0,0 +1,21 @@
+use crate::remotes;
+
+use clap::ArgMatches;
+use colored::Colorize;
+
+pub async fn list(_matches: &ArgMatches) -> Result<i32, anyhow::Error> {
+    let remotes = remotes::read();
+
+    if remotes.is_empty() {
+        println!("{}", "no remotes saved".red());
+        return Ok(1);
+    }
+
+    for (name, remote) in remotes {
+        println!();
+        println!("{}", name.cyan().bold().underline());
+        println!("  {} {}", "address:".bright_black(), remote.address.cyan());
+    }
+
+    Ok(0)
+}
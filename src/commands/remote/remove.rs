@@ -0,0 +1,39 @@
+use crate::remotes;
+
+use clap::ArgMatches;
+use colored::Colorize;
+
+pub async fn remove(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
+    let name = matches.get_one::<String>("name").expect("required");
+
+    let mut remotes = remotes::read();
+
+    if remotes.remove(name).is_none() {
+        println!(
+            "{} {} {}",
+            "remote".red(),
+            name.cyan(),
+            "does not exist!".red()
+        );
+        return Ok(1);
+    }
+
+    println!(
+        "{} {} {}",
+        "removing remote".bright_black(),
+        name.cyan(),
+        "...".bright_black()
+    );
+
+    remotes::write(&remotes)?;
+
+    println!(
+        "{} {} {} {}",
+        "removing remote".bright_black(),
+        name.cyan(),
+        "...".bright_black(),
+        "DONE".green().bold()
+    );
+
+    Ok(0)
+}
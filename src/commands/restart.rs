@@ -0,0 +1,112 @@
+use crate::{commands::recover, config, detached, java};
+
+use clap::ArgMatches;
+use colored::Colorize;
+use tokio::io::AsyncWriteExt;
+
+pub async fn restart(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
+    let timeout = *matches.get_one::<u64>("timeout").expect("required");
+    let countdown = *matches.get_one::<u64>("countdown").expect("required");
+    let config = config::Config::new(".mcvcli.json", false);
+
+    if !detached::is_running() {
+        println!(
+            "{} {}",
+            "server is not running, use".red(),
+            "mcvcli start --detached".cyan()
+        );
+        return Ok(1);
+    }
+
+    // A server orphaned by its daemon is taken over by a new one first, which then restarts it.
+    if let Some(pid) = detached::orphan() {
+        recover::recover_orphan(pid).await?;
+    }
+
+    // The daemon respawns the server with this spec, so config changes (ram, flags, java
+    // version) are picked up without losing attached consoles.
+    let [binary, java_home] = java::binary(config.java_version).await?;
+    let spec = detached::Spec::new(&config, binary, java_home);
+
+    let Ok(connection) = detached::connect().await else {
+        println!(
+            "{} {}",
+            "daemon is unreachable, use".red(),
+            "mcvcli stop".cyan()
+        );
+        return Ok(1);
+    };
+    let (mut reader, mut writer) = tokio::io::split(connection);
+
+    if countdown > 0 {
+        println!(
+            "{}",
+            format!("restarting server in {countdown}s ({timeout}s before being killed) ...")
+                .bright_black()
+        );
+        println!(
+            "{} {}",
+            "(ctrl+c leaves the countdown running, cancel it with".bright_black(),
+            "mcvcli stop --cancel)".cyan()
+        );
+    } else {
+        println!(
+            "{}",
+            format!("restarting server ({timeout}s before being killed) ...").bright_black()
+        );
+    }
+
+    detached::write_frame(
+        &mut writer,
+        detached::TAG_RESTART_SERVER,
+        &serde_json::to_vec(&detached::StopRequest {
+            timeout,
+            countdown,
+            spec: Some(spec),
+        })?,
+    )
+    .await?;
+
+    let mut stdout = tokio::io::stdout();
+    let mut stderr = tokio::io::stderr();
+
+    loop {
+        match detached::read_frame(&mut reader).await {
+            Ok((detached::TAG_STDOUT, data)) => {
+                let _ = stdout.write_all(&data).await;
+                let _ = stdout.flush().await;
+            }
+            Ok((detached::TAG_STDERR, data)) => {
+                let _ = stderr.write_all(&data).await;
+                let _ = stderr.flush().await;
+            }
+            Ok((detached::TAG_RESTART, _)) => break,
+            Ok((detached::TAG_EXIT, data)) => {
+                let code = data
+                    .as_slice()
+                    .try_into()
+                    .map(i32::from_be_bytes)
+                    .unwrap_or(0);
+
+                println!();
+                println!("{} {}", "server has stopped with code".red(), code);
+                return Ok(1);
+            }
+            Ok(_) => {}
+            Err(_) => {
+                println!();
+                println!("{}", "disconnected from server".red());
+                return Ok(1);
+            }
+        }
+    }
+
+    println!();
+    println!(
+        "{} {}",
+        "restarting server ...".bright_black(),
+        "DONE".green().bold()
+    );
+
+    Ok(0)
+}
@@ -0,0 +1,104 @@
+use crate::{backups, config, schedule};
+
+use clap::ArgMatches;
+use colored::Colorize;
+use rand::{RngExt, distr::Alphanumeric};
+
+pub async fn add(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
+    let expression = matches.get_one::<String>("expression").expect("required");
+    let action = matches.get_one::<String>("action").expect("required");
+    let argument = matches.get_one::<String>("argument");
+    let timeout = *matches.get_one::<u64>("timeout").expect("required");
+    let countdown = *matches.get_one::<u64>("countdown").expect("required");
+    let _config = config::Config::new(".mcvcli.json", false);
+
+    let cron = match schedule::Cron::parse(expression) {
+        Ok(cron) => cron,
+        Err(err) => {
+            println!(
+                "{} {} {}",
+                "invalid schedule expression".red(),
+                expression.cyan(),
+                format!("({err})").red()
+            );
+            return Ok(1);
+        }
+    };
+
+    let action = match action.as_str() {
+        "command" => {
+            let Some(command) = argument else {
+                println!("{}", "the command action requires a console command".red());
+                return Ok(1);
+            };
+
+            schedule::Action::Command {
+                command: command.to_string(),
+            }
+        }
+        "restart" => schedule::Action::Restart { timeout, countdown },
+        "backup" => {
+            let format = argument.map(String::as_str).unwrap_or("zip");
+            let Ok(format) =
+                serde_json::from_str::<backups::BackupFormat>(&format!("\"{format}\""))
+            else {
+                println!(
+                    "{} {}",
+                    "invalid format, accepted values:".red(),
+                    "(zip, tar, tar.gz, tar.xz)".cyan()
+                );
+                return Ok(1);
+            };
+
+            schedule::Action::Backup { format }
+        }
+        _ => unreachable!(),
+    };
+
+    let id: String = rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(6)
+        .map(char::from)
+        .collect::<String>()
+        .to_lowercase();
+
+    // A schedule that can't be read must not be overwritten with just the new job.
+    let mut jobs = match schedule::read() {
+        Ok(jobs) => jobs,
+        Err(err) => {
+            println!("{} {err:#}", "failed to read the schedule:".red());
+            return Ok(1);
+        }
+    };
+
+    println!("{}", "adding scheduled job ...".bright_black());
+
+    jobs.push(schedule::Job {
+        id: id.clone(),
+        expression: expression.to_string(),
+        action,
+    });
+    schedule::write(&jobs)?;
+
+    println!(
+        "{} {}",
+        "adding scheduled job ...".bright_black(),
+        "DONE".green().bold()
+    );
+    println!();
+
+    println!("{} {}", "added job".bright_black(), id.cyan());
+    if let Some(next) = cron.next_after(&chrono::Local::now()) {
+        println!(
+            "{} {}",
+            "next run:".bright_black(),
+            next.format("%Y-%m-%d %H:%M").to_string().cyan()
+        );
+    }
+    println!(
+        "{}",
+        "(jobs are run by the detached server, use mcvcli start --detached)".bright_black()
+    );
+
+    Ok(0)
+}
@@ -0,0 +1,49 @@
+use crate::{config, schedule};
+
+use clap::ArgMatches;
+use colored::Colorize;
+
+pub async fn list(_matches: &ArgMatches) -> Result<i32, anyhow::Error> {
+    let _config = config::Config::new(".mcvcli.json", false);
+
+    let jobs = match schedule::read() {
+        Ok(jobs) => jobs,
+        Err(err) => {
+            println!("{} {err:#}", "failed to read the schedule:".red());
+            return Ok(1);
+        }
+    };
+
+    if jobs.is_empty() {
+        println!("{}", "no scheduled jobs found".red());
+        return Ok(1);
+    }
+
+    let now = chrono::Local::now();
+
+    for job in jobs {
+        println!();
+        println!("{}", job.id.cyan().bold().underline());
+
+        println!("  {} {}", "schedule:".bright_black(), job.expression.cyan());
+        println!(
+            "  {} {}",
+            "action:  ".bright_black(),
+            job.action.describe().cyan()
+        );
+
+        let next = schedule::Cron::parse(&job.expression)
+            .ok()
+            .and_then(|cron| cron.next_after(&now));
+        println!(
+            "  {} {}",
+            "next run:".bright_black(),
+            match next {
+                Some(next) => next.format("%Y-%m-%d %H:%M").to_string().cyan(),
+                None => "never".red(),
+            }
+        );
+    }
+
+    Ok(0)
+}
@@ -0,0 +1,75 @@
+use crate::{config, schedule};
+
+use clap::ArgMatches;
+use colored::Colorize;
+use dialoguer::{FuzzySelect, theme::ColorfulTheme};
+
+pub async fn remove(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
+    let id = matches.get_one::<String>("id");
+    let _config = config::Config::new(".mcvcli.json", false);
+
+    let mut jobs = match schedule::read() {
+        Ok(jobs) => jobs,
+        Err(err) => {
+            println!("{} {err:#}", "failed to read the schedule:".red());
+            return Ok(1);
+        }
+    };
+
+    let index = if let Some(id) = id {
+        match jobs.iter().position(|job| job.id == *id) {
+            Some(index) => index,
+            None => {
+                println!(
+                    "{} {} {}",
+                    "scheduled job".red(),
+                    id.cyan(),
+                    "does not exist!".red()
+                );
+                return Ok(1);
+            }
+        }
+    } else {
+        if jobs.is_empty() {
+            println!("{}", "no scheduled jobs to remove".red());
+            return Ok(1);
+        }
+
+        let index = FuzzySelect::with_theme(&ColorfulTheme::default())
+            .with_prompt("Select scheduled job to remove")
+            .items(
+                jobs.iter()
+                    .map(|job| {
+                        format!("{} ({}, {})", job.id, job.expression, job.action.describe())
+                    })
+                    .collect::<Vec<String>>(),
+            )
+            .default(0)
+            .max_length(5)
+            .interact()?;
+        println!();
+
+        index
+    };
+
+    let job = jobs.remove(index);
+
+    println!(
+        "{} {} {}",
+        "removing scheduled job".bright_black(),
+        job.id.cyan(),
+        "...".bright_black()
+    );
+
+    schedule::write(&jobs)?;
+
+    println!(
+        "{} {} {} {}",
+        "removing scheduled job".bright_black(),
+        job.id.cyan(),
+        "...".bright_black(),
+        "DONE".green().bold()
+    );
+
+    Ok(0)
+}
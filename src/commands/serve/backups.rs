@@ -0,0 +1,143 @@
+use super::{ApiError, ApiResult, require_stopped};
+use crate::{
+    backups::{self, BackupFormat},
+    commands::webhook,
+    config::Config,
+    detached,
+    hooks::{self, Environment, Hook},
+    webhooks::{Event, Notification},
+};
+
+use axum::{Json, extract::Path, http::StatusCode};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+/// Like the `backup` commands, notifies the webhooks without making the request wait for them.
+fn notify(notification: Notification) {
+    if let Ok(config) = super::config() {
+        tokio::spawn(async move { webhook::notify(&config, notification).await });
+    }
+}
+
+/// Runs a backup or restore hook, failing with 409 if a pre hook fails, like for starts.
+async fn run_hook(hook: Hook, config: &Config, name: &str) -> ApiResult<()> {
+    let environment = Environment {
+        backup: Some(name.to_string()),
+        ..Environment::new(config)
+    };
+
+    match hooks::run(hook, &config.hooks, &environment).await.result {
+        Err(err) if matches!(hook, Hook::PreBackup | Hook::PreRestore) => Err(ApiError::new(
+            StatusCode::CONFLICT,
+            format!("the {} hook failed: {err:#}", hook.name()),
+        )),
+        _ => Ok(()),
+    }
+}
+
+pub async fn list() -> Json<Value> {
+    Json(Value::Array(
+        backups::list()
+            .into_iter()
+            .map(|backup| {
+                json!({
+                    "name": backup.name,
+                    "path": backup.path,
+                    "size": backup.size,
+                    "format": backups::extension(&backup.format),
+                    "created": backup.created.to_rfc3339(),
+                })
+            })
+            .collect(),
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct CreateBody {
+    name: String,
+    #[serde(default = "default_format")]
+    format: BackupFormat,
+}
+
+fn default_format() -> BackupFormat {
+    BackupFormat::Zip
+}
+
+pub async fn create(Json(body): Json<CreateBody>) -> ApiResult<(StatusCode, Json<Value>)> {
+    if body.name.is_empty() || body.name.contains(['/', '\\']) || body.name.starts_with('.') {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "invalid backup name",
+        ));
+    }
+
+    if backups::list()
+        .iter()
+        .any(|backup| backup.name == body.name)
+    {
+        return Err(ApiError::new(
+            StatusCode::CONFLICT,
+            format!("backup {} already exists", body.name),
+        ));
+    }
+
+    let name = body.name.clone();
+    let format = backups::extension(&body.format);
+    let config = super::config()?;
+    run_hook(Hook::PreBackup, &config, &name).await?;
+
+    if super::is_running().await {
+        backups::create_live(&body.name, body.format, |mut request| async move {
+            request.identity = Some(detached::Identity::current(Some("api".to_string()), false));
+            detached::exec(&request).await
+        })
+        .await?;
+    } else {
+        tokio::task::spawn_blocking(move || backups::create(&body.name, &body.format))
+            .await
+            .map_err(anyhow::Error::from)??;
+    }
+
+    run_hook(Hook::PostBackup, &config, &name).await?;
+
+    notify(
+        Notification::new(Event::Backup, format!("backup {name} created"))
+            .field("name", &name)
+            .field("format", format),
+    );
+
+    Ok((StatusCode::CREATED, Json(json!({ "name": name }))))
+}
+
+/// Replaces the server directory with a backup, like `mcvcli backup restore`.
+pub async fn restore(Path(name): Path<String>) -> ApiResult<Json<Value>> {
+    require_stopped().await?;
+
+    let Some(backup) = backups::list()
+        .into_iter()
+        .find(|backup| backup.name == name)
+    else {
+        return Err(ApiError::new(
+            StatusCode::NOT_FOUND,
+            format!("backup {name} does not exist"),
+        ));
+    };
+
+    let config = super::config()?;
+    run_hook(Hook::PreRestore, &config, &name).await?;
+
+    tokio::task::spawn_blocking(move || {
+        backups::wipe()?;
+        backups::restore(&backup)
+    })
+    .await
+    .map_err(anyhow::Error::from)??;
+
+    run_hook(Hook::PostRestore, &config, &name).await?;
+
+    notify(
+        Notification::new(Event::Restore, format!("backup {name} restored")).field("name", &name),
+    );
+
+    Ok(Json(json!({ "restored": name })))
+}
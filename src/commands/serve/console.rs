@@ -0,0 +1,135 @@
+use crate::{crash, detached};
+
+use axum::{
+    extract::{
+        Query,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    response::Response,
+};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+#[derive(Deserialize)]
+pub struct ConsoleQuery {
+    #[serde(default)]
+    read_only: bool,
+}
+
+/// Attaches to the server console over a WebSocket, like `mcvcli attach`. Output, events and
+/// restarts arrive as JSON text messages, text sent by the client is written to stdin.
+pub async fn console(ws: WebSocketUpgrade, Query(query): Query<ConsoleQuery>) -> Response {
+    ws.on_upgrade(move |socket| bridge(socket, query.read_only))
+}
+
+async fn send(socket: &mut WebSocket, message: Value) -> bool {
+    socket
+        .send(Message::Text(message.to_string().into()))
+        .await
+        .is_ok()
+}
+
+async fn bridge(mut socket: WebSocket, read_only: bool) {
+    let connection = match super::is_running().await {
+        true => detached::connect_to(None).await.ok(),
+        false => None,
+    };
+    let Some(connection) = connection else {
+        send(
+            &mut socket,
+            json!({ "type": "error", "error": "the server is not running" }),
+        )
+        .await;
+        let _ = socket.send(Message::Close(None)).await;
+        return;
+    };
+    let (mut reader, mut writer) = tokio::io::split(connection);
+
+    if detached::write_frame(
+        &mut writer,
+        detached::TAG_ATTACH,
+        &serde_json::to_vec(&detached::Identity::current(
+            Some("api".to_string()),
+            read_only,
+        ))
+        .unwrap_or_default(),
+    )
+    .await
+    .is_err()
+    {
+        return;
+    }
+
+    // Frames are read on their own task, `read_frame` can't be cancelled halfway by the select.
+    let (frame_tx, mut frame_rx) = tokio::sync::mpsc::unbounded_channel();
+    let reader_task = tokio::spawn(async move {
+        while let Ok(frame) = detached::read_frame(&mut reader).await {
+            if frame_tx.send(frame).is_err() {
+                break;
+            }
+        }
+    });
+
+    loop {
+        tokio::select! {
+            frame = frame_rx.recv() => {
+                let message = match frame {
+                    Some((detached::TAG_STDOUT, data)) => {
+                        json!({ "type": "stdout", "data": String::from_utf8_lossy(&data) })
+                    }
+                    Some((detached::TAG_STDERR, data)) => {
+                        json!({ "type": "stderr", "data": String::from_utf8_lossy(&data) })
+                    }
+                    Some((detached::TAG_REPLAY_END, _)) => json!({ "type": "replay_end" }),
+                    Some((detached::TAG_EVENT, data)) => match serde_json::from_slice::<Value>(&data) {
+                        Ok(event) => json!({ "type": "event", "event": event }),
+                        Err(_) => continue,
+                    },
+                    Some((detached::TAG_RESTART, data)) => {
+                        match serde_json::from_slice::<detached::RestartNotice>(&data) {
+                            Ok(notice) => json!({ "type": "restart", "notice": notice }),
+                            Err(_) => continue,
+                        }
+                    }
+                    Some((detached::TAG_CRASH, data)) => {
+                        match serde_json::from_slice::<crash::Summary>(&data) {
+                            Ok(summary) => json!({ "type": "crash", "crash": summary }),
+                            Err(_) => continue,
+                        }
+                    }
+                    Some((detached::TAG_EXIT, data)) => {
+                        let code = data.as_slice().try_into().map(i32::from_be_bytes).unwrap_or(0);
+                        send(&mut socket, json!({ "type": "exit", "code": code })).await;
+                        break;
+                    }
+                    Some(_) => continue,
+                    None => break,
+                };
+
+                if !send(&mut socket, message).await {
+                    break;
+                }
+            }
+            message = socket.recv() => match message {
+                Some(Ok(Message::Text(text))) if !read_only => {
+                    let mut line = text.to_string();
+                    if !line.ends_with('\n') {
+                        line.push('\n');
+                    }
+
+                    if detached::write_frame(&mut writer, detached::TAG_STDIN, line.as_bytes())
+                        .await
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+
+    reader_task.abort();
+    let _ = socket.send(Message::Close(None)).await;
+}
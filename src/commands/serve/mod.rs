@@ -0,0 +1,169 @@
+mod backups;
+mod console;
+mod profiles;
+mod server;
+mod version;
+
+use crate::{config, detached};
+
+use axum::{
+    Json, Router,
+    extract::{Query, Request, State},
+    http::{StatusCode, header},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use clap::ArgMatches;
+use colored::Colorize;
+use rand::{RngExt, distr::Alphanumeric};
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// An error response, `{"error": "..."}` with a status code.
+pub struct ApiError(StatusCode, String);
+
+impl ApiError {
+    pub fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        ApiError(status, message.into())
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(err: anyhow::Error) -> Self {
+        ApiError(StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+    }
+}
+
+pub type ApiResult<T> = Result<T, ApiError>;
+
+/// The config of the server in this directory, read for every request so changes made with the
+/// other commands apply right away.
+pub fn config() -> ApiResult<config::Config> {
+    config::Config::load(".mcvcli.json").map_err(ApiError::from)
+}
+
+/// Whether the server in this directory is running. This scans every process, so it runs on the
+/// blocking pool instead of holding up the other requests.
+pub async fn is_running() -> bool {
+    tokio::task::spawn_blocking(detached::is_running)
+        .await
+        .unwrap_or(false)
+}
+
+/// Fails with 409 if the server in this directory is running, for changes that need it stopped.
+pub async fn require_stopped() -> ApiResult<()> {
+    if is_running().await {
+        return Err(ApiError::new(
+            StatusCode::CONFLICT,
+            "the server is currently running",
+        ));
+    }
+
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct TokenQuery {
+    token: Option<String>,
+}
+
+/// Requests need the token as a bearer token, or as a `token` query parameter for WebSocket
+/// clients in browsers, which can't set headers.
+async fn authenticate(State(token): State<Arc<String>>, request: Request, next: Next) -> Response {
+    let bearer = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::to_string);
+    // Decoded, so a token with reserved characters also works when percent-encoded.
+    let query = Query::<TokenQuery>::try_from_uri(request.uri())
+        .ok()
+        .and_then(|Query(query)| query.token);
+
+    match bearer.or(query) {
+        Some(given) if detached::constant_time_eq(given.as_bytes(), token.as_bytes()) => {
+            next.run(request).await
+        }
+        _ => ApiError::new(StatusCode::UNAUTHORIZED, "missing or wrong token").into_response(),
+    }
+}
+
+pub async fn serve(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
+    let address = matches.get_one::<String>("address").expect("required");
+    let _config = config::Config::new(".mcvcli.json", false);
+
+    let (token, generated) = match matches
+        .get_one::<String>("token")
+        .cloned()
+        .or_else(|| std::env::var("MCVCLI_SERVE_TOKEN").ok())
+    {
+        Some(token) => (token, false),
+        None => (
+            rand::rng()
+                .sample_iter(&Alphanumeric)
+                .take(32)
+                .map(char::from)
+                .collect(),
+            true,
+        ),
+    };
+
+    let api = Router::new()
+        .route("/status", get(server::status))
+        .route("/start", post(server::start))
+        .route("/stop", post(server::stop))
+        .route("/restart", post(server::restart))
+        .route("/cancel", post(server::cancel))
+        .route("/exec", post(server::exec))
+        .route("/console", get(console::console))
+        .route("/backups", get(backups::list).post(backups::create))
+        .route("/backups/{name}/restore", post(backups::restore))
+        .route("/profiles", get(profiles::list))
+        .route("/profiles/{name}/use", post(profiles::r#use))
+        .route("/version", get(version::version))
+        .layer(middleware::from_fn_with_state(
+            Arc::new(token.clone()),
+            authenticate,
+        ));
+    let app = Router::new().nest("/api", api);
+
+    let listener = match tokio::net::TcpListener::bind(address).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            println!(
+                "{} {}{} {}",
+                "failed to listen on".red(),
+                address.cyan(),
+                ":".red(),
+                err
+            );
+            return Ok(1);
+        }
+    };
+
+    println!(
+        "{} {}",
+        "serving the api on".bright_black(),
+        format!("http://{address}/api").cyan()
+    );
+    if generated {
+        println!("{} {}", "generated token:".bright_black(), token.cyan());
+    }
+    println!("{}", "(press ctrl-c to stop serving)".bright_black());
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+
+    Ok(0)
+}
@@ -0,0 +1,38 @@
+use super::{ApiError, ApiResult, require_stopped};
+use crate::profiles;
+
+use axum::{Json, extract::Path, http::StatusCode};
+use serde_json::{Value, json};
+
+pub async fn list() -> ApiResult<Json<Value>> {
+    let config = super::config()?;
+
+    Ok(Json(json!({
+        "current": config.profile_name,
+        "profiles": profiles::list(),
+    })))
+}
+
+/// Switches to another profile, like `mcvcli profile use`.
+pub async fn r#use(Path(name): Path<String>) -> ApiResult<Json<Value>> {
+    let config = super::config()?;
+    require_stopped().await?;
+
+    if config.profile_name == name {
+        return Err(ApiError::new(
+            StatusCode::CONFLICT,
+            format!("profile {name} is currently in use"),
+        ));
+    }
+
+    if !profiles::list().contains(&name) {
+        return Err(ApiError::new(
+            StatusCode::NOT_FOUND,
+            format!("profile {name} does not exist"),
+        ));
+    }
+
+    profiles::switch(&config.profile_name, &name)?;
+
+    Ok(Json(json!({ "current": name })))
+}
@@ -0,0 +1,289 @@
+use super::{ApiError, ApiResult};
+use crate::{
+    commands::start::spawn_detached,
+    detached,
+    hooks::{self, Environment, Hook},
+    java,
+};
+
+use axum::{Json, http::StatusCode};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::path::Path;
+
+async fn require_running() -> ApiResult<()> {
+    if !super::is_running().await {
+        return Err(ApiError::new(
+            StatusCode::CONFLICT,
+            "the server is not running",
+        ));
+    }
+
+    Ok(())
+}
+
+async fn connect() -> ApiResult<Box<dyn detached::Connection>> {
+    detached::connect_to(None)
+        .await
+        .map_err(|_| ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "the daemon is unreachable"))
+}
+
+fn exit_code(data: &[u8]) -> i32 {
+    data.try_into().map(i32::from_be_bytes).unwrap_or(0)
+}
+
+pub async fn status() -> ApiResult<Json<Value>> {
+    let config = super::config()?;
+
+    if !super::is_running().await {
+        return Ok(Json(json!({
+            "running": false,
+            "profile": config.profile_name,
+        })));
+    }
+
+    let status = detached::status(None).await.ok();
+    let process = status.as_ref().and_then(|status| status.process.as_ref());
+
+    Ok(Json(json!({
+        "running": true,
+        "profile": config.profile_name,
+        "pid": process.map(|process| process.pid),
+        "memory": process.map(|process| process.memory),
+        "start_time": process.map(|process| process.start_time),
+        "ready_at": status.as_ref().and_then(|status| status.ready_at),
+        "players": status.map(|status| status.players).unwrap_or_default(),
+    })))
+}
+
+pub async fn start() -> ApiResult<Json<Value>> {
+    let config = super::config()?;
+
+    if super::is_running().await {
+        return Err(ApiError::new(
+            StatusCode::CONFLICT,
+            "the server is already running",
+        ));
+    }
+
+    if !std::fs::read_to_string("eula.txt")
+        .unwrap_or_default()
+        .contains("eula=true")
+    {
+        return Err(ApiError::new(
+            StatusCode::CONFLICT,
+            "the minecraft eula has not been accepted",
+        ));
+    }
+
+    if !Path::new(&config.jar_file).exists() {
+        return Err(ApiError::new(
+            StatusCode::CONFLICT,
+            format!("the server jar {} does not exist", config.jar_file),
+        ));
+    }
+
+    let [binary, java_home] = java::binary(config.java_version).await?;
+
+    if let Err(err) = hooks::run(Hook::PreStart, &config.hooks, &Environment::new(&config))
+        .await
+        .result
+    {
+        return Err(ApiError::new(
+            StatusCode::CONFLICT,
+            format!("the pre-start hook failed: {err:#}"),
+        ));
+    }
+
+    if !spawn_detached(&detached::Spec::new(&config, binary, java_home)).await? {
+        return Err(ApiError::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "failed to start the server, check .mcvcli.detached/latest.log",
+        ));
+    }
+
+    Ok(Json(json!({ "started": true })))
+}
+
+#[derive(Deserialize)]
+pub struct StopBody {
+    #[serde(default = "default_timeout")]
+    timeout: u64,
+    #[serde(default)]
+    countdown: u64,
+}
+
+fn default_timeout() -> u64 {
+    20
+}
+
+/// Stops the server. Without a countdown the response is sent once it has exited.
+pub async fn stop(body: Option<Json<StopBody>>) -> ApiResult<(StatusCode, Json<Value>)> {
+    let StopBody { timeout, countdown } = body.map_or(
+        StopBody {
+            timeout: default_timeout(),
+            countdown: 0,
+        },
+        |Json(body)| body,
+    );
+    require_running().await?;
+
+    let (mut reader, mut writer) = tokio::io::split(connect().await?);
+    detached::write_frame(
+        &mut writer,
+        detached::TAG_STOP,
+        &serde_json::to_vec(&detached::StopRequest {
+            timeout,
+            countdown,
+            spec: None,
+        })
+        .unwrap_or_default(),
+    )
+    .await
+    .map_err(anyhow::Error::from)?;
+
+    if countdown > 0 {
+        return Ok((
+            StatusCode::ACCEPTED,
+            Json(json!({ "countdown": countdown })),
+        ));
+    }
+
+    let code = loop {
+        match detached::read_frame(&mut reader).await {
+            Ok((detached::TAG_EXIT, data)) => break Some(exit_code(&data)),
+            Ok(_) => {}
+            Err(_) => break None,
+        }
+    };
+
+    for _ in 0..50 {
+        if !super::is_running().await {
+            break;
+        }
+
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    }
+
+    Ok((StatusCode::OK, Json(json!({ "code": code }))))
+}
+
+/// Restarts the server with the current config. Without a countdown the response is sent once
+/// the new process has been spawned.
+pub async fn restart(body: Option<Json<StopBody>>) -> ApiResult<(StatusCode, Json<Value>)> {
+    let StopBody { timeout, countdown } = body.map_or(
+        StopBody {
+            timeout: default_timeout(),
+            countdown: 0,
+        },
+        |Json(body)| body,
+    );
+    let config = super::config()?;
+    require_running().await?;
+
+    let [binary, java_home] = java::binary(config.java_version).await?;
+    let spec = detached::Spec::new(&config, binary, java_home);
+
+    let (mut reader, mut writer) = tokio::io::split(connect().await?);
+    detached::write_frame(
+        &mut writer,
+        detached::TAG_RESTART_SERVER,
+        &serde_json::to_vec(&detached::StopRequest {
+            timeout,
+            countdown,
+            spec: Some(spec),
+        })
+        .unwrap_or_default(),
+    )
+    .await
+    .map_err(anyhow::Error::from)?;
+
+    if countdown > 0 {
+        return Ok((
+            StatusCode::ACCEPTED,
+            Json(json!({ "countdown": countdown })),
+        ));
+    }
+
+    loop {
+        match detached::read_frame(&mut reader).await {
+            Ok((detached::TAG_RESTART, _)) => {
+                return Ok((StatusCode::OK, Json(json!({ "restarted": true }))));
+            }
+            Ok((detached::TAG_EXIT, data)) => {
+                return Err(ApiError::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!(
+                        "the server stopped with code {} instead of restarting",
+                        exit_code(&data)
+                    ),
+                ));
+            }
+            Ok(_) => {}
+            Err(_) => {
+                return Err(ApiError::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "disconnected from the daemon",
+                ));
+            }
+        }
+    }
+}
+
+pub async fn cancel() -> ApiResult<Json<Value>> {
+    require_running().await?;
+
+    let (mut reader, mut writer) = tokio::io::split(connect().await?);
+    detached::write_frame(&mut writer, detached::TAG_CANCEL, &[])
+        .await
+        .map_err(anyhow::Error::from)?;
+
+    loop {
+        match detached::read_frame(&mut reader)
+            .await
+            .map_err(anyhow::Error::from)?
+        {
+            (detached::TAG_CANCEL_RESULT, data) => {
+                return Ok(Json(json!({ "cancelled": data.first() == Some(&1) })));
+            }
+            _ => continue,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ExecBody {
+    command: String,
+    #[serde(default = "default_quiet_ms")]
+    quiet_ms: u64,
+    #[serde(default = "default_timeout_ms")]
+    timeout_ms: u64,
+}
+
+fn default_quiet_ms() -> u64 {
+    500
+}
+
+fn default_timeout_ms() -> u64 {
+    5000
+}
+
+/// Runs a console command, like `mcvcli exec`.
+pub async fn exec(Json(body): Json<ExecBody>) -> ApiResult<Json<Value>> {
+    require_running().await?;
+
+    let request = detached::ExecRequest {
+        command: body.command,
+        quiet_ms: body.quiet_ms,
+        timeout_ms: body.timeout_ms,
+        identity: Some(detached::Identity::current(Some("api".to_string()), false)),
+        until: Vec::new(),
+    };
+    let output = detached::exec(&request).await?;
+
+    // The daemon waits the full timeout for the first output, so none means it timed out.
+    Ok(Json(json!({
+        "output": String::from_utf8_lossy(&output),
+        "timed_out": output.is_empty(),
+    })))
+}
@@ -0,0 +1,40 @@
+use super::ApiResult;
+use crate::jar;
+
+use axum::Json;
+use serde_json::{Value, json};
+
+/// The installed jar and modpack, like `mcvcli version`.
+pub async fn version() -> ApiResult<Json<Value>> {
+    let config = super::config()?;
+
+    let (jar, modpack) = match jar::detect(".", &config).await {
+        Some(([build, latest], versions, modpack)) => (
+            json!({
+                "type": build.r#type,
+                "version": build.version_id.as_ref().or(build.project_version_id.as_ref()),
+                "build": build.name,
+                "latest_version": jar::is_latest_version(&build, &versions),
+                "latest_build": build.uuid == latest.uuid,
+            }),
+            modpack.map(|modpack| {
+                json!({
+                    "name": modpack.title,
+                    "description": modpack.description,
+                    "project_id": modpack.id,
+                    "version_id": config.modpack_version,
+                    "latest": modpack.versions.last() == config.modpack_version.as_ref(),
+                    "downloads": modpack.downloads,
+                })
+            }),
+        ),
+        None => (Value::Null, None),
+    };
+
+    Ok(Json(json!({
+        "jar_file": config.jar_file,
+        "java_version": config.java_version,
+        "jar": jar,
+        "modpack": modpack,
+    })))
+}
@@ -0,0 +1,148 @@
+use super::{resolve, run, systemctl};
+use crate::{
+    config, detached,
+    service::{self, Init, Options},
+};
+
+use clap::ArgMatches;
+use colored::Colorize;
+use std::path::Path;
+
+pub async fn install(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
+    let restart = matches.get_one::<String>("restart").expect("required");
+    let enable = !*matches.get_one::<bool>("no_enable").expect("required");
+    let _config = config::Config::new(".mcvcli.json", false);
+
+    let Some(service) = resolve(matches) else {
+        return Ok(1);
+    };
+
+    let mut environment = Vec::new();
+    for key in service::PASSTHROUGH_ENV {
+        if let Ok(value) = std::env::var(key) {
+            environment.push((key.to_string(), value));
+        }
+    }
+    for variable in matches.get_many::<String>("env").unwrap_or_default() {
+        let Some((key, value)) = variable.split_once('=').filter(|(key, _)| !key.is_empty()) else {
+            println!(
+                "{} {} {}",
+                "invalid environment variable".red(),
+                variable.cyan(),
+                "(use KEY=VALUE)".red()
+            );
+            return Ok(1);
+        };
+
+        environment.retain(|(existing, _)| existing != key);
+        environment.push((key.to_string(), value.to_string()));
+    }
+
+    let options = Options {
+        executable: std::env::current_exe()?,
+        user: detached::current_user(),
+        restart: restart.clone(),
+        environment,
+    };
+
+    let path = service.path();
+    println!(
+        "{} {} {}",
+        "writing".bright_black(),
+        path.display().to_string().cyan(),
+        "...".bright_black()
+    );
+
+    std::fs::create_dir_all(&service.unit_dir)?;
+    std::fs::write(&path, service.render(&options))?;
+
+    #[cfg(unix)]
+    if service.init == Init::OpenRc {
+        use std::os::unix::fs::PermissionsExt;
+
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+    }
+
+    println!(
+        "{} {} {} {}",
+        "writing".bright_black(),
+        path.display().to_string().cyan(),
+        "...".bright_black(),
+        "DONE".green().bold()
+    );
+
+    if !std::fs::read_to_string("eula.txt")
+        .unwrap_or_default()
+        .contains("eula=true")
+    {
+        println!(
+            "{} {} {}",
+            "the eula has not been accepted yet, run".yellow(),
+            "mcvcli start --eula".cyan(),
+            "once, the service can't answer the prompt".yellow()
+        );
+    }
+
+    if service.custom_dir || !enable {
+        return Ok(0);
+    }
+
+    println!(
+        "{} {} {}",
+        "enabling".bright_black(),
+        service.name.cyan(),
+        "...".bright_black()
+    );
+
+    let result = match service.init {
+        Init::Systemd => match systemctl(&service, &["daemon-reload"]).await {
+            Ok(_) => systemctl(&service, &["enable", &service.file_name()]).await,
+            Err(err) => Err(err),
+        },
+        Init::OpenRc => run("rc-update", &["add", &service.name, "default"]).await,
+    };
+    if let Err(err) = result {
+        println!("{} {}", "failed to enable the service:".red(), err);
+        return Ok(1);
+    }
+
+    println!(
+        "{} {} {} {}",
+        "enabling".bright_black(),
+        service.name.cyan(),
+        "...".bright_black(),
+        "DONE".green().bold()
+    );
+
+    // User units only start at boot for users with lingering enabled.
+    if service.init == Init::Systemd
+        && !service.system
+        && let Some(user) = &options.user
+        && !Path::new("/var/lib/systemd/linger").join(user).exists()
+    {
+        println!(
+            "{} {} {}",
+            "user units only start at boot with lingering, run".yellow(),
+            format!("loginctl enable-linger {user}").cyan(),
+            "to enable it".yellow()
+        );
+    }
+
+    println!();
+    println!(
+        "{} {} {}",
+        "use".bright_black(),
+        match service.init {
+            Init::Systemd => format!(
+                "systemctl{} start {}",
+                if service.system { "" } else { " --user" },
+                service.name
+            ),
+            Init::OpenRc => format!("rc-service {} start", service.name),
+        }
+        .cyan(),
+        "to start the server through the service".bright_black()
+    );
+
+    Ok(0)
+}
@@ -0,0 +1,117 @@
+pub mod install;
+pub mod status;
+pub mod uninstall;
+
+use crate::service::{self, Init, Service};
+
+use clap::ArgMatches;
+use colored::Colorize;
+use std::path::PathBuf;
+
+/// The service selected by `--init`, `--system`, `--name` and `--unit-dir` for the server in the
+/// current directory. Problems are printed, so `None` means the command failed.
+pub fn resolve(matches: &ArgMatches) -> Option<Service> {
+    let unit_dir = matches.get_one::<String>("unit_dir").map(PathBuf::from);
+    let init = match matches.get_one::<String>("init") {
+        Some(init) => Init::parse(init)?,
+        // A custom unit directory is mostly for generating units elsewhere, systemd is the
+        // common case there.
+        None => match (Init::detect(), &unit_dir) {
+            (Some(init), _) => init,
+            (None, Some(_)) => Init::Systemd,
+            (None, None) => {
+                println!(
+                    "{} {}",
+                    "could not detect systemd or openrc, use".red(),
+                    "--init".cyan()
+                );
+                return None;
+            }
+        },
+    };
+    let system = *matches.get_one::<bool>("system").expect("required") || is_root();
+
+    let directory = match std::env::current_dir() {
+        Ok(directory) => directory,
+        Err(err) => {
+            println!("{} {}", "failed to get the current directory:".red(), err);
+            return None;
+        }
+    };
+
+    let name = matches
+        .get_one::<String>("name")
+        .cloned()
+        .unwrap_or_else(|| service::default_name(&directory));
+    if !service::is_valid_name(&name) {
+        println!(
+            "{} {} {}",
+            "invalid service name".red(),
+            name.cyan(),
+            "(use letters, digits, _, - and .)".red()
+        );
+        return None;
+    }
+
+    let custom_dir = unit_dir.is_some();
+    let Some(unit_dir) = unit_dir.or_else(|| service::default_unit_dir(init, system)) else {
+        println!(
+            "{} {}",
+            "could not find the unit directory, use".red(),
+            "--unit-dir".cyan()
+        );
+        return None;
+    };
+
+    Some(Service {
+        init,
+        system,
+        name,
+        directory,
+        unit_dir,
+        custom_dir,
+    })
+}
+
+fn is_root() -> bool {
+    #[cfg(unix)]
+    {
+        unsafe { libc::geteuid() == 0 }
+    }
+    #[cfg(not(unix))]
+    {
+        false
+    }
+}
+
+/// Runs a command of the init system and returns its trimmed output, or the error.
+pub async fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = tokio::process::Command::new(program)
+        .args(args)
+        .output()
+        .await
+        .map_err(|err| format!("failed to run {program}: {err}"))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    match output.status.success() {
+        true => Ok(stdout),
+        false => {
+            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            Err(match stderr.is_empty() {
+                true => stdout,
+                false => stderr,
+            })
+        }
+    }
+}
+
+/// `systemctl` for the scope of the service.
+pub async fn systemctl(service: &Service, args: &[&str]) -> Result<String, String> {
+    let mut all = Vec::with_capacity(args.len() + 1);
+    if !service.system {
+        all.push("--user");
+    }
+    all.extend_from_slice(args);
+
+    run("systemctl", &all).await
+}
@@ -0,0 +1,93 @@
+use super::{resolve, run, systemctl};
+use crate::{config, detached, service::Init};
+
+use clap::ArgMatches;
+use colored::Colorize;
+
+pub async fn status(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
+    let _config = config::Config::new(".mcvcli.json", false);
+
+    let Some(service) = resolve(matches) else {
+        return Ok(1);
+    };
+
+    let path = service.path();
+    if !path.exists() {
+        println!(
+            "{} {} {}",
+            "no service is installed at".red(),
+            path.display().to_string().cyan(),
+            "(see mcvcli service install)".red()
+        );
+        return Ok(1);
+    }
+
+    println!(
+        "{} ({})",
+        service.name.cyan().bold().underline(),
+        service.describe()
+    );
+    println!(
+        "  {} {}",
+        "file:        ".bright_black(),
+        path.display().to_string().cyan()
+    );
+
+    // `is-enabled` and `is-active` exit non-zero for disabled and inactive units, but still print
+    // the state.
+    if !service.custom_dir {
+        let state = |result: Result<String, String>| match result {
+            Ok(state) => state.green(),
+            Err(state) => state.red(),
+        };
+
+        match service.init {
+            Init::Systemd => {
+                println!(
+                    "  {} {}",
+                    "enabled:     ".bright_black(),
+                    state(systemctl(&service, &["is-enabled", &service.file_name()]).await)
+                );
+                println!(
+                    "  {} {}",
+                    "active:      ".bright_black(),
+                    state(systemctl(&service, &["is-active", &service.file_name()]).await)
+                );
+            }
+            Init::OpenRc => {
+                let runlevels = run("rc-update", &["show", "--all"])
+                    .await
+                    .unwrap_or_default()
+                    .lines()
+                    .filter_map(|line| line.split_once('|'))
+                    .find(|(name, _)| name.trim() == service.name)
+                    .map(|(_, runlevels)| runlevels.trim().to_string())
+                    .filter(|runlevels| !runlevels.is_empty());
+                println!(
+                    "  {} {}",
+                    "runlevels:   ".bright_black(),
+                    match runlevels {
+                        Some(runlevels) => runlevels.green(),
+                        None => "none".red(),
+                    }
+                );
+                println!(
+                    "  {} {}",
+                    "status:      ".bright_black(),
+                    state(run("rc-service", &[&service.name, "status"]).await)
+                );
+            }
+        }
+    }
+
+    println!(
+        "  {} {}",
+        "server:      ".bright_black(),
+        match detached::is_running() {
+            true => "running".green(),
+            false => "not running".red(),
+        }
+    );
+
+    Ok(0)
+}
@@ -0,0 +1,71 @@
+use super::{resolve, run, systemctl};
+use crate::{config, service::Init};
+
+use clap::ArgMatches;
+use colored::Colorize;
+
+pub async fn uninstall(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
+    let _config = config::Config::new(".mcvcli.json", false);
+
+    let Some(service) = resolve(matches) else {
+        return Ok(1);
+    };
+
+    let path = service.path();
+    if !path.exists() {
+        println!(
+            "{} {} {}",
+            "service".red(),
+            path.display().to_string().cyan(),
+            "does not exist!".red()
+        );
+        return Ok(1);
+    }
+
+    // The server keeps running, it is only no longer started at boot.
+    if !service.custom_dir {
+        println!(
+            "{} {} {}",
+            "disabling".bright_black(),
+            service.name.cyan(),
+            "...".bright_black()
+        );
+
+        let result = match service.init {
+            Init::Systemd => systemctl(&service, &["disable", &service.file_name()]).await,
+            Init::OpenRc => run("rc-update", &["del", &service.name, "default"]).await,
+        };
+        match result {
+            Ok(_) => println!(
+                "{} {} {} {}",
+                "disabling".bright_black(),
+                service.name.cyan(),
+                "...".bright_black(),
+                "DONE".green().bold()
+            ),
+            Err(err) => println!("{} {}", "failed to disable the service:".red(), err),
+        }
+    }
+
+    println!(
+        "{} {} {}",
+        "removing".bright_black(),
+        path.display().to_string().cyan(),
+        "...".bright_black()
+    );
+
+    std::fs::remove_file(&path)?;
+    if !service.custom_dir && service.init == Init::Systemd {
+        let _ = systemctl(&service, &["daemon-reload"]).await;
+    }
+
+    println!(
+        "{} {} {} {}",
+        "removing".bright_black(),
+        path.display().to_string().cyan(),
+        "...".bright_black(),
+        "DONE".green().bold()
+    );
+
+    Ok(0)
+}
@@ -1,19 +1,206 @@
 use crate::api::{self, Progress};
-use crate::{config, detached, java};
+use crate::console::{Console, Input};
+use crate::logs::Event;
+use crate::{
+    commands::{crash::format_summary, recover},
+    config, crash, detached,
+    hooks::{self, Environment, Hook, Hooks},
+    java, ping,
+};
 
 use clap::ArgMatches;
 use colored::Colorize;
 use dialoguer::{Confirm, theme::ColorfulTheme};
 use human_bytes::human_bytes;
-use std::{fs::File, io::Write, path::Path};
-use tokio::io::AsyncReadExt;
+use std::{fs::File, io::Write, path::Path, sync::Arc};
+use tokio::io::{AsyncRead, AsyncReadExt};
 use tokio::{io::AsyncWriteExt, process::Command, sync::Mutex};
 
+enum Readiness {
+    Ready,
+    Exited(i32),
+    TimedOut,
+}
+
+/// Waits for the detached server to finish loading, either by seeing its `Done (...)!` line or by
+/// getting an answer to a status ping on the `server-port` from `server.properties`.
+async fn wait_ready(timeout: u64) -> Readiness {
+    let Ok(connection) = detached::connect().await else {
+        return Readiness::Exited(1);
+    };
+    let (mut reader, mut writer) = tokio::io::split(connection);
+
+    if detached::write_frame(&mut writer, detached::TAG_FOLLOW, &[])
+        .await
+        .is_err()
+    {
+        return Readiness::Exited(1);
+    }
+
+    if let Ok(status) = detached::status(None).await
+        && status.ready_at.is_some()
+    {
+        return Readiness::Ready;
+    }
+
+    // Frames are read on their own task, `read_frame` can't be cancelled halfway by the select.
+    let (frame_tx, mut frame_rx) = tokio::sync::mpsc::unbounded_channel();
+    let reader_task = tokio::spawn(async move {
+        while let Ok(frame) = detached::read_frame(&mut reader).await {
+            if frame_tx.send(frame).is_err() {
+                break;
+            }
+        }
+    });
+
+    let deadline = tokio::time::Instant::now() + tokio::time::Duration::from_secs(timeout);
+    let mut ping_interval = tokio::time::interval(tokio::time::Duration::from_secs(2));
+
+    let readiness = loop {
+        tokio::select! {
+            frame = frame_rx.recv() => match frame {
+                Some((detached::TAG_EVENT, payload)) => {
+                    if let Ok(Event::Ready { .. }) = serde_json::from_slice(&payload) {
+                        break Readiness::Ready;
+                    }
+                }
+                Some((detached::TAG_RESTART, payload)) => {
+                    if let Ok(notice) = serde_json::from_slice::<detached::RestartNotice>(&payload)
+                        && !notice.requested
+                    {
+                        break Readiness::Exited(notice.code);
+                    }
+                }
+                Some((detached::TAG_EXIT, payload)) => {
+                    break Readiness::Exited(
+                        payload
+                            .as_slice()
+                            .try_into()
+                            .map(i32::from_be_bytes)
+                            .unwrap_or(0),
+                    );
+                }
+                Some(_) => {}
+                None => break Readiness::Exited(1),
+            },
+            _ = ping_interval.tick() => {
+                if ping::ping_local(tokio::time::Duration::from_secs(2)).await {
+                    break Readiness::Ready;
+                }
+            }
+            _ = tokio::time::sleep_until(deadline) => break Readiness::TimedOut,
+        }
+    };
+
+    reader_task.abort();
+
+    readiness
+}
+
+/// Copies server output to the console until the pipe closes.
+fn forward_output(
+    mut output: impl AsyncRead + Unpin + Send + 'static,
+    console: Arc<Console>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut buffer = [0; 4096];
+
+        while let Ok(n @ 1..) = output.read(&mut buffer).await {
+            console.print(&buffer[..n]);
+            console.parse(&buffer[..n]);
+        }
+    })
+}
+
+/// Writes the spec and spawns the daemon, then waits for it to come up and report its state.
+pub async fn spawn_detached(spec: &detached::Spec) -> Result<bool, anyhow::Error> {
+    detached::write_spec(spec)?;
+    detached::spawn_daemon(None)?;
+
+    for _ in 0..50 {
+        if detached::is_running() {
+            return Ok(true);
+        }
+
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    }
+
+    Ok(false)
+}
+
+/// Runs a hook with its output above the console prompt if there is one, returns whether all of
+/// its commands succeeded.
+pub async fn run_hook(
+    hook: Hook,
+    hooks: &Hooks,
+    environment: &Environment,
+    console: Option<&Console>,
+) -> bool {
+    if hooks.commands(hook).is_empty() {
+        return true;
+    }
+
+    let print = |message: String| match console {
+        Some(console) => console.notice(message),
+        None => println!("{message}"),
+    };
+
+    let running = format!("running {} hooks ...", hook.name());
+    print(running.bright_black().to_string());
+
+    let mut outcome = hooks::run(hook, hooks, environment).await;
+    if !outcome.output.is_empty() && !outcome.output.ends_with(b"\n") {
+        outcome.output.push(b'\n');
+    }
+    match console {
+        Some(console) => console.print(&outcome.output),
+        None => {
+            let _ = std::io::stdout().write_all(&outcome.output);
+        }
+    }
+
+    match outcome.result {
+        Ok(()) => {
+            print(format!(
+                "{} {}",
+                running.bright_black(),
+                "DONE".green().bold()
+            ));
+            true
+        }
+        Err(err) => {
+            print(format!(
+                "{} {}",
+                running.bright_black(),
+                "FAILED".red().bold()
+            ));
+            print(format!(
+                "{} {err:#}",
+                format!("{} hook failed:", hook.name()).red()
+            ));
+            false
+        }
+    }
+}
+
+fn print_log_tail(lines: usize) {
+    let content = std::fs::read_to_string(detached::log_path()).unwrap_or_default();
+    let all: Vec<&str> = content.lines().collect();
+
+    println!();
+    for line in &all[all.len().saturating_sub(lines)..] {
+        println!("  {}", line.bright_black());
+    }
+    println!();
+}
+
 pub async fn start(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
     let config = config::Config::new(".mcvcli.json", false);
     let auto_agree_eula = *matches.get_one::<bool>("eula").expect("required");
     let detached = *matches.get_one::<bool>("detached").expect("required");
     let timeout = *matches.get_one::<u64>("timeout").expect("required");
+    let wait = *matches.get_one::<bool>("wait").expect("required");
+    let wait_timeout = *matches.get_one::<u64>("wait_timeout").expect("required");
 
     let eula_accepted = std::fs::read_to_string("eula.txt")
         .unwrap_or_default()
@@ -34,6 +221,19 @@ pub async fn start(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
         std::fs::write("eula.txt", "eula=true\n")?;
     }
 
+    if let Some(pid) = detached::orphan() {
+        if !recover::recover_orphan(pid).await? {
+            return Ok(1);
+        }
+
+        println!(
+            "{} {}",
+            "server was already running, use".bright_black(),
+            "mcvcli attach".cyan()
+        );
+        return Ok(0);
+    }
+
     if detached::is_running() {
         println!(
             "{} {}",
@@ -147,11 +347,24 @@ pub async fn start(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
         }
     }
 
+    let environment = Environment::new(&config);
+    if !run_hook(Hook::PreStart, &config.hooks, &environment, None).await {
+        return Ok(1);
+    }
+
     println!();
     println!("{}", "starting the minecraft server...".yellow());
     println!("{command}");
 
     if !detached {
+        // Without a terminal the server's output and our stdin are passed through untouched.
+        let (console, input) = match Console::start(Some(detached::history_path())) {
+            Some((console, input)) => (Some(Arc::new(console)), Some(input)),
+            None => (None, None),
+        };
+
+        let started_at = std::time::SystemTime::now();
+        let started = std::time::Instant::now();
         let mut child = {
             let mut command = Command::new(binary);
 
@@ -163,8 +376,13 @@ pub async fn start(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
             command.args(config.extra_args);
             command.env("JAVA_HOME", java_home);
             command.stdin(std::process::Stdio::piped());
-            command.stdout(std::process::Stdio::inherit());
-            command.stderr(std::process::Stdio::inherit());
+            if console.is_some() {
+                command.stdout(std::process::Stdio::piped());
+                command.stderr(std::process::Stdio::piped());
+            } else {
+                command.stdout(std::process::Stdio::inherit());
+                command.stderr(std::process::Stdio::inherit());
+            }
             command.kill_on_drop(true);
 
             // Process group 0 to make sure child processes are also killed
@@ -173,8 +391,38 @@ pub async fn start(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
 
             command.spawn()?
         };
+        let environment = Environment {
+            pid: child.id(),
+            ..environment
+        };
+
+        // The foreground server's output isn't always ours to read, so readiness comes from pings.
+        let post_start = tokio::spawn({
+            let hooks = config.hooks.clone();
+            let environment = environment.clone();
+            let console = console.clone();
+
+            async move {
+                while !ping::ping_local(tokio::time::Duration::from_secs(2)).await {
+                    tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+                }
+
+                run_hook(Hook::PostStart, &hooks, &environment, console.as_deref()).await;
+            }
+        });
+
+        let mut output_tasks = Vec::new();
+        if let Some(console) = &console {
+            if let Some(stdout) = child.stdout.take() {
+                output_tasks.push(forward_output(stdout, Arc::clone(console)));
+            }
+            if let Some(stderr) = child.stderr.take() {
+                output_tasks.push(forward_output(stderr, Arc::clone(console)));
+            }
+        }
 
         let child_kill_notifier = tokio::sync::Notify::new();
+        let interrupt_notifier = tokio::sync::Notify::new();
         let child_stdin = Mutex::new(
             child
                 .stdin
@@ -183,7 +431,10 @@ pub async fn start(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
         );
 
         let stop_future = async {
-            let _ = tokio::signal::ctrl_c().await;
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = interrupt_notifier.notified() => {}
+            }
 
             println!();
             println!();
@@ -193,13 +444,33 @@ pub async fn start(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
             );
             println!();
 
+            // The pre-stop hook runs on the stop timeout, so a slow hook can't hold up the stop.
+            let deadline = tokio::time::Instant::now() + tokio::time::Duration::from_secs(timeout);
+            if tokio::time::timeout_at(
+                deadline,
+                run_hook(
+                    Hook::PreStop,
+                    &config.hooks,
+                    &environment,
+                    console.as_deref(),
+                ),
+            )
+            .await
+            .is_err()
+            {
+                println!(
+                    "{}",
+                    "pre-stop hooks did not finish before the stop timeout".red()
+                );
+            }
+
             let _ = child_stdin
                 .lock()
                 .await
-                .write_all((config.stop_command + "\n").as_bytes())
+                .write_all((config.stop_command.clone() + "\n").as_bytes())
                 .await;
 
-            tokio::time::sleep(tokio::time::Duration::from_secs(timeout)).await;
+            tokio::time::sleep_until(deadline).await;
 
             println!(
                 "{}",
@@ -216,6 +487,27 @@ pub async fn start(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
         };
 
         let stdin_future = async {
+            if let Some(mut input) = input {
+                loop {
+                    match input.recv().await {
+                        Some(Input::Line(line)) => {
+                            let _ = child_stdin
+                                .lock()
+                                .await
+                                .write_all(format!("{line}\n").as_bytes())
+                                .await;
+                        }
+                        Some(Input::Interrupted) => {
+                            // Stopping is left to `stop_future`, this future finishing would
+                            // skip the stop timeout.
+                            interrupt_notifier.notify_one();
+                            std::future::pending::<()>().await;
+                        }
+                        Some(Input::Closed) | None => return,
+                    }
+                }
+            }
+
             let mut buffer = [0; 1024];
             let mut stdin = tokio::io::stdin();
 
@@ -254,6 +546,15 @@ pub async fn start(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
             }
         };
 
+        post_start.abort();
+
+        // Let the output readers drain whatever the server printed last before the terminal is
+        // handed back.
+        for task in output_tasks {
+            let _ = tokio::time::timeout(tokio::time::Duration::from_secs(1), task).await;
+        }
+        drop(console);
+
         println!();
         println!(
             "{} {}",
@@ -262,35 +563,38 @@ pub async fn start(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
         );
 
         println!("{} {}", "server has stopped with code".red(), code);
+
+        let crash =
+            (code != 0).then(|| crash::detect(started_at, &crash::server_log_since(started_at)));
+        if let Some(summary) = &crash {
+            println!();
+            println!("{}", "server crashed".red());
+            println!("{}", format_summary(summary));
+        }
+
+        let crashed = crash.is_some();
+        let environment = Environment {
+            exit_code: Some(code),
+            exception: crash.as_ref().and_then(|summary| summary.exception.clone()),
+            ..environment
+        };
+
+        let _ = crash::record(&crash::Exit {
+            time: chrono::Utc::now().timestamp(),
+            code,
+            uptime_secs: started.elapsed().as_secs(),
+            crash,
+        });
+
+        if crashed {
+            run_hook(Hook::OnCrash, &config.hooks, &environment, None).await;
+        }
+        run_hook(Hook::PostStop, &config.hooks, &environment, None).await;
     } else {
         // Hand the server off to a detached `mcvcli daemon` supervisor. The daemon owns java,
         // continuously drains its output (so the pipe never fills) and exposes a control socket
         // that `attach`/`stop` connect to.
-        detached::write_spec(&detached::Spec {
-            binary,
-            java_home,
-            jar_file: config.jar_file.clone(),
-            ram_mb: config.ram_mb,
-            extra_flags: config.extra_flags.clone(),
-            extra_args: config.extra_args.clone(),
-            stop_command: config.stop_command.clone(),
-            log_max_bytes: config.detached_log_max_mb.saturating_mul(1024 * 1024),
-        })?;
-
-        detached::spawn_daemon()?;
-
-        // Wait for the daemon to come up and report its state.
-        let mut started = false;
-        for _ in 0..50 {
-            if detached::is_running() {
-                started = true;
-                break;
-            }
-
-            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-        }
-
-        if !started {
+        if !spawn_detached(&detached::Spec::new(&config, binary, java_home)).await? {
             println!(
                 "{}",
                 "failed to start detached server, check .mcvcli.detached/latest.log".red()
@@ -298,6 +602,50 @@ pub async fn start(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
             return Ok(1);
         }
 
+        if wait {
+            println!(
+                "{}",
+                "waiting for the server to finish loading ...".bright_black()
+            );
+
+            match wait_ready(wait_timeout).await {
+                Readiness::Ready => println!(
+                    "{} {}",
+                    "waiting for the server to finish loading ...".bright_black(),
+                    "DONE".green().bold()
+                ),
+                Readiness::Exited(code) => {
+                    println!(
+                        "{} {}",
+                        "waiting for the server to finish loading ...".bright_black(),
+                        "FAILED".red().bold()
+                    );
+                    print_log_tail(20);
+                    println!(
+                        "{} {}",
+                        "server has stopped during startup with code".red(),
+                        code
+                    );
+                    return Ok(1);
+                }
+                Readiness::TimedOut => {
+                    println!(
+                        "{} {}",
+                        "waiting for the server to finish loading ...".bright_black(),
+                        "FAILED".red().bold()
+                    );
+                    print_log_tail(20);
+                    println!(
+                        "{} {} {}",
+                        "server did not finish loading within".red(),
+                        format!("{wait_timeout}s").cyan(),
+                        "(it is still running)".red()
+                    );
+                    return Ok(1);
+                }
+            }
+        }
+
         println!(
             "{} {}",
             "server has started in detached mode, use".green(),
@@ -1,22 +1,208 @@
-use crate::{config, detached};
+use crate::{
+    cgroup,
+    commands::crash::format_summary,
+    config, crash,
+    detached::{self, DaemonStatus, Remote},
+    logs, remotes, usage,
+};
 
 use chrono::DateTime;
 use clap::ArgMatches;
 use colored::Colorize;
 use human_bytes::human_bytes;
 
-pub async fn status(_matches: &ArgMatches) -> Result<i32, anyhow::Error> {
+fn summarize(values: impl Iterator<Item = f64>) -> (f64, f64, f64) {
+    let (mut min, mut max, mut sum, mut count) = (f64::MAX, f64::MIN, 0.0, 0);
+    for value in values {
+        min = min.min(value);
+        max = max.max(value);
+        sum += value;
+        count += 1;
+    }
+
+    (min, sum / count.max(1) as f64, max)
+}
+
+fn print_summary(label: &str, (min, avg, max): (f64, f64, f64), format: impl Fn(f64) -> String) {
+    println!(
+        "  {} {} {}  {} {}  {} {}",
+        label.bright_black(),
+        "min".bright_black(),
+        format(min).cyan(),
+        "avg".bright_black(),
+        format(avg).cyan(),
+        "max".bright_black(),
+        format(max).cyan()
+    );
+}
+
+fn history(period: &str, config: &config::Config) -> Result<i32, anyhow::Error> {
+    let Some(duration) = logs::parse_duration(period) else {
+        println!(
+            "{} {} {}",
+            "invalid period".red(),
+            period.cyan(),
+            "(use e.g. 30m, 24h or 7d)".red()
+        );
+        return Ok(1);
+    };
+
+    let samples = usage::read((chrono::Utc::now() - duration).timestamp());
+    if samples.is_empty() {
+        println!(
+            "{} {}",
+            "no resource usage recorded in the last".red(),
+            period.cyan()
+        );
+        return Ok(1);
+    }
+
+    let interval = usage::SAMPLE_INTERVAL_SECS as f64;
+
+    println!(
+        "{} (last {}, {} samples)",
+        config.profile_name.cyan().bold().underline(),
+        period.cyan(),
+        samples.len().to_string().cyan()
+    );
+    print_summary(
+        "cpu:         ",
+        summarize(samples.iter().map(|sample| sample.cpu as f64)),
+        |cpu| format!("{cpu:.1}%"),
+    );
+    print_summary(
+        "memory:      ",
+        summarize(samples.iter().map(|sample| sample.memory as f64)),
+        human_bytes,
+    );
+    print_summary(
+        "threads:     ",
+        summarize(samples.iter().map(|sample| sample.threads as f64)),
+        |threads| format!("{threads:.0}"),
+    );
+    print_summary(
+        "disk read:   ",
+        summarize(
+            samples
+                .iter()
+                .map(|sample| sample.disk_read as f64 / interval),
+        ),
+        |rate| format!("{}/s", human_bytes(rate)),
+    );
+    print_summary(
+        "disk written:",
+        summarize(
+            samples
+                .iter()
+                .map(|sample| sample.disk_written as f64 / interval),
+        ),
+        |rate| format!("{}/s", human_bytes(rate)),
+    );
+    println!(
+        "  {} {} MB",
+        "ram (config):".bright_black(),
+        config.ram_mb.to_string().cyan()
+    );
+
+    Ok(0)
+}
+
+async fn watch(config: &config::Config) -> Result<i32, anyhow::Error> {
+    let mut sampler = usage::Sampler::new();
+    let mut last = std::time::Instant::now();
+
+    loop {
+        let Some(state) = detached::read_state().filter(|_| detached::is_running()) else {
+            println!();
+            println!("{}", "server has stopped".red());
+            return Ok(0);
+        };
+
+        let sample = sampler.sample(state.java_pid);
+        let elapsed = last.elapsed().as_secs_f64().max(0.001);
+        last = std::time::Instant::now();
+        let status = detached::status(None).await.ok();
+
+        print!("\x1b[2J\x1b[H");
+        println!(
+            "{} (pid {})",
+            config.profile_name.cyan().bold().underline(),
+            state.java_pid
+        );
+
+        if let Some(sample) = sample {
+            println!(
+                "  {} {}",
+                "cpu:         ".bright_black(),
+                format!("{:.1}%", sample.cpu).cyan()
+            );
+            println!(
+                "  {} {} ({} MB configured)",
+                "memory:      ".bright_black(),
+                human_bytes(sample.memory as f64).cyan(),
+                config.ram_mb.to_string().cyan()
+            );
+            println!(
+                "  {} {}",
+                "threads:     ".bright_black(),
+                sample.threads.to_string().cyan()
+            );
+            println!(
+                "  {} {}/s read, {}/s written",
+                "disk:        ".bright_black(),
+                human_bytes(sample.disk_read as f64 / elapsed).cyan(),
+                human_bytes(sample.disk_written as f64 / elapsed).cyan()
+            );
+        }
+
+        if let Some(status) = status {
+            println!(
+                "  {} {}",
+                "players:     ".bright_black(),
+                status.players.len().to_string().cyan()
+            );
+        }
+
+        println!();
+        println!("{}", "press ctrl+c to exit".bright_black());
+
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    }
+}
+
+pub async fn status(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
+    let watch = *matches.get_one::<bool>("watch").expect("required");
+    let period = matches.get_one::<String>("history");
+
+    match remotes::resolve(matches) {
+        Ok(Some(remote)) => return remote_status(&remote).await,
+        Ok(None) => {}
+        Err(err) => {
+            println!("{}", err.to_string().red());
+            return Ok(1);
+        }
+    }
+
     let config = config::Config::new(".mcvcli.json", false);
 
+    if let Some(period) = period {
+        return history(period, &config);
+    }
+
     if !detached::is_running() {
         println!(
             "{} {}",
             "server is not running, use".red(),
             "mcvcli start --detached".cyan()
         );
+        print_last_crash(i64::MIN);
         return Ok(1);
     }
 
+    if watch {
+        return self::watch(&config).await;
+    }
+
     println!("{}", "getting server status ...".bright_black());
 
     let state = detached::read_state()
@@ -46,11 +232,102 @@ pub async fn status(_matches: &ArgMatches) -> Result<i32, anyhow::Error> {
         human_bytes(process.memory() as f64).cyan()
     );
 
-    let uptime = chrono::Utc::now().timestamp() - process.start_time() as i64;
+    print_uptime(process.start_time())?;
+
+    let group = cgroup::of_process(state.java_pid);
+    if group.is_none()
+        && (config.cgroup_memory_max_mb.is_some()
+            || config.cgroup_cpu_max.is_some()
+            || config.cgroup_pids_max.is_some())
+    {
+        println!(
+            "  {} {}",
+            "limits:      ".bright_black(),
+            "not applied (see the daemon log)".yellow()
+        );
+    }
+
+    if let Some(group) = group
+        && let Some(before) = cgroup::usage(&group)
+    {
+        tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+        let before_usec = before.cpu_usage_usec;
+        let usage = cgroup::usage(&group).unwrap_or(before);
+        let cpu = usage.cpu_usage_usec.saturating_sub(before_usec) as f64 / 250_000.0;
+
+        println!(
+            "  {} {} / {}",
+            "memory limit:".bright_black(),
+            human_bytes(usage.memory_current as f64).cyan(),
+            usage
+                .memory_max
+                .map_or("unlimited".to_string(), |max| human_bytes(max as f64))
+                .cyan()
+        );
+        println!(
+            "  {} {} / {} cores",
+            "cpu limit:   ".bright_black(),
+            format!("{cpu:.2}").cyan(),
+            usage
+                .cpu_max
+                .map_or("unlimited".to_string(), |max| format!("{max:.2}"))
+                .cyan()
+        );
+        println!(
+            "  {} {} / {}",
+            "pids limit:  ".bright_black(),
+            usage.pids_current.to_string().cyan(),
+            usage
+                .pids_max
+                .map_or("unlimited".to_string(), |max| max.to_string())
+                .cyan()
+        );
+    }
+
+    if let Ok(status) = detached::status(None).await {
+        print_daemon_status(status);
+    }
+
+    // Crashes the daemon restarted the server after.
+    if let Some(daemon) = sys.process(sysinfo::Pid::from(state.daemon_pid as usize)) {
+        print_last_crash(daemon.start_time() as i64);
+    }
+
+    Ok(0)
+}
+
+/// Prints the last exit of the server if it crashed at or after `since`.
+fn print_last_crash(since: i64) {
+    let Some(exit) = crash::history().pop().filter(|exit| exit.time >= since) else {
+        return;
+    };
+    let Some(summary) = &exit.crash else {
+        return;
+    };
+
+    println!();
+    println!(
+        "{} {} {}",
+        "last crash:".red(),
+        DateTime::from_timestamp(exit.time, 0)
+            .map(|time| {
+                time.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or_default()
+            .cyan(),
+        format!("(code {})", exit.code).bright_black()
+    );
+    println!("{}", format_summary(summary));
+}
+
+fn print_uptime(start_time: u64) -> Result<(), anyhow::Error> {
+    let uptime = chrono::Utc::now().timestamp() - start_time as i64;
     println!(
         "  {} {} ({}h {}m {}s)",
         "start time:  ".bright_black(),
-        DateTime::from_timestamp(process.start_time() as i64, 0)
+        DateTime::from_timestamp(start_time as i64, 0)
             .ok_or_else(|| anyhow::anyhow!("invalid process start time"))?
             .format("%Y-%m-%d %H:%M:%S")
             .to_string()
@@ -60,5 +337,75 @@ pub async fn status(_matches: &ArgMatches) -> Result<i32, anyhow::Error> {
         (uptime % 60).to_string().cyan()
     );
 
+    Ok(())
+}
+
+fn print_daemon_status(status: DaemonStatus) {
+    match status.ready_at {
+        Some(ready_at) => {
+            let ready = chrono::Utc::now().timestamp() - ready_at;
+            println!(
+                "  {} {}h {}m {}s ago",
+                "ready:       ".bright_black(),
+                (ready / 3600).to_string().cyan(),
+                ((ready % 3600) / 60).to_string().cyan(),
+                (ready % 60).to_string().cyan()
+            );
+        }
+        None => println!(
+            "  {} {}",
+            "ready:       ".bright_black(),
+            "starting ...".yellow()
+        ),
+    }
+
+    println!(
+        "  {} {}",
+        "players:     ".bright_black(),
+        status.players.len().to_string().cyan()
+    );
+    for player in status.players {
+        println!("    {}", player.cyan());
+    }
+}
+
+/// The status of a server on another machine, as far as its daemon can tell.
+async fn remote_status(remote: &Remote) -> Result<i32, anyhow::Error> {
+    println!("{}", "getting server status ...".bright_black());
+
+    let status = match detached::status(Some(remote)).await {
+        Ok(status) => status,
+        Err(err) => {
+            println!("{} {}", "failed to connect to remote:".red(), err);
+            return Ok(1);
+        }
+    };
+
+    println!(
+        "{} {}",
+        "getting server status ...".bright_black(),
+        "DONE".green().bold()
+    );
+    println!();
+
+    match &status.process {
+        Some(process) => {
+            println!(
+                "{} (pid {})",
+                remote.address.cyan().bold().underline(),
+                process.pid
+            );
+            println!(
+                "  {} {}",
+                "memory usage:".bright_black(),
+                human_bytes(process.memory as f64).cyan()
+            );
+            print_uptime(process.start_time)?;
+        }
+        None => println!("{}", remote.address.cyan().bold().underline()),
+    }
+
+    print_daemon_status(status);
+
     Ok(0)
 }
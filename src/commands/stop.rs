@@ -1,11 +1,15 @@
-use crate::detached;
+use crate::{
+    commands::recover,
+    detached::{self, Remote},
+    registry, remotes,
+};
 
 use clap::ArgMatches;
 use colored::Colorize;
 use tokio::io::AsyncWriteExt;
 
-/// Best-effort kill of the server and supervisor when the control socket is unreachable (a wedged
-/// or already-killed daemon, the latter having orphaned the java child).
+/// Best-effort kill of the server and supervisor when the control socket is unreachable and the
+/// server can't be recovered (a wedged daemon, or a java process that is already on its way out).
 async fn force_kill() {
     let Some(state) = detached::read_state() else {
         detached::cleanup();
@@ -40,10 +44,182 @@ async fn force_kill() {
     detached::cleanup();
 }
 
+/// Starts a countdown on the server in the current directory without waiting for it to run out.
+async fn request_countdown(timeout: u64, countdown: u64) -> Result<(), anyhow::Error> {
+    let connection = detached::connect().await?;
+    let (_reader, mut writer) = tokio::io::split(connection);
+
+    detached::write_frame(
+        &mut writer,
+        detached::TAG_STOP,
+        &serde_json::to_vec(&detached::StopRequest {
+            timeout,
+            countdown,
+            spec: None,
+        })?,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Stops every server in the machine-wide registry, one after another. Countdowns are started on
+/// all of them first, so they run out at the same time.
+async fn stop_all(timeout: u64, countdown: u64) -> Result<i32, anyhow::Error> {
+    let entries = registry::list();
+
+    if entries.is_empty() {
+        println!("{}", "no servers are running".red());
+        return Ok(1);
+    }
+
+    if countdown > 0 {
+        println!(
+            "{}",
+            format!("starting a {countdown}s countdown on all servers ...").bright_black()
+        );
+
+        for entry in &entries {
+            if std::env::set_current_dir(&entry.directory).is_ok() {
+                let _ = request_countdown(timeout, countdown).await;
+            }
+        }
+
+        println!(
+            "{} {}",
+            format!("starting a {countdown}s countdown on all servers ...").bright_black(),
+            "DONE".green().bold()
+        );
+        tokio::time::sleep(tokio::time::Duration::from_secs(countdown)).await;
+    }
+
+    let mut failed = Vec::new();
+    for entry in entries {
+        println!();
+        println!(
+            "{} ({})",
+            entry.directory.cyan().bold().underline(),
+            entry.profile
+        );
+
+        if std::env::set_current_dir(&entry.directory).is_err() {
+            println!("{}", "server directory does not exist anymore".red());
+            failed.push(entry.directory);
+            continue;
+        }
+
+        // Servers whose countdown ran out may be gone already, the rest are stopped right away.
+        if countdown > 0 && !detached::is_running() {
+            println!(
+                "{} {}",
+                "stopping server ...".bright_black(),
+                "DONE".green().bold()
+            );
+            continue;
+        }
+
+        // One server failing to stop must not leave the rest running.
+        match stop_server(timeout, 0, None).await {
+            Ok(0) => {}
+            Ok(_) => failed.push(entry.directory),
+            Err(err) => {
+                println!("{} {err:#}", "failed to stop server:".red());
+                failed.push(entry.directory);
+            }
+        }
+    }
+
+    if failed.is_empty() {
+        return Ok(0);
+    }
+
+    println!();
+    println!("{}", "failed to stop:".red());
+    for directory in failed {
+        println!("  {}", directory.cyan());
+    }
+
+    Ok(1)
+}
+
+async fn cancel(remote: Option<&Remote>) -> Result<i32, anyhow::Error> {
+    if remote.is_none() && !detached::is_running() {
+        println!(
+            "{} {}",
+            "server is not running, use".red(),
+            "mcvcli start --detached".cyan()
+        );
+        return Ok(1);
+    }
+
+    let connection = match detached::connect_to(remote).await {
+        Ok(connection) => connection,
+        Err(err) if remote.is_some() => {
+            println!("{} {}", "failed to connect to remote:".red(), err);
+            return Ok(1);
+        }
+        Err(_) => {
+            println!("{}", "daemon is unreachable".red());
+            return Ok(1);
+        }
+    };
+    let (mut reader, mut writer) = tokio::io::split(connection);
+
+    println!("{}", "cancelling countdown ...".bright_black());
+
+    detached::write_frame(&mut writer, detached::TAG_CANCEL, &[]).await?;
+
+    let cancelled = loop {
+        match detached::read_frame(&mut reader).await? {
+            (detached::TAG_CANCEL_RESULT, data) => break data.first() == Some(&1),
+            _ => continue,
+        }
+    };
+
+    if !cancelled {
+        println!("{}", "no countdown is running".red());
+        return Ok(1);
+    }
+
+    println!(
+        "{} {}",
+        "cancelling countdown ...".bright_black(),
+        "DONE".green().bold()
+    );
+
+    Ok(0)
+}
+
 pub async fn stop(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
     let timeout = *matches.get_one::<u64>("timeout").expect("required");
+    let all = *matches.get_one::<bool>("all").expect("required");
+    let countdown = *matches.get_one::<u64>("countdown").expect("required");
+    let remote = match remotes::resolve(matches) {
+        Ok(remote) => remote,
+        Err(err) => {
+            println!("{}", err.to_string().red());
+            return Ok(1);
+        }
+    };
+
+    if *matches.get_one::<bool>("cancel").expect("required") {
+        return cancel(remote.as_ref()).await;
+    }
+
+    if all {
+        return stop_all(timeout, countdown).await;
+    }
+
+    stop_server(timeout, countdown, remote.as_ref()).await
+}
 
-    if !detached::is_running() {
+/// Stops the detached server in the current directory, or a remote one.
+async fn stop_server(
+    timeout: u64,
+    countdown: u64,
+    remote: Option<&Remote>,
+) -> Result<i32, anyhow::Error> {
+    if remote.is_none() && !detached::is_running() {
         println!(
             "{} {}",
             "server is not running, use".red(),
@@ -52,8 +228,20 @@ pub async fn stop(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
         return Ok(1);
     }
 
-    let connection = match detached::connect().await {
+    // A server orphaned by its daemon is taken over by a new one first, so it gets stopped
+    // gracefully instead of killed.
+    if remote.is_none()
+        && let Some(pid) = detached::orphan()
+    {
+        recover::recover_orphan(pid).await?;
+    }
+
+    let connection = match detached::connect_to(remote).await {
         Ok(connection) => connection,
+        Err(err) if remote.is_some() => {
+            println!("{} {}", "failed to connect to remote:".red(), err);
+            return Ok(1);
+        }
         Err(_) => {
             println!(
                 "{}",
@@ -71,12 +259,34 @@ pub async fn stop(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
 
     let (mut reader, mut writer) = tokio::io::split(connection);
 
-    println!(
-        "{}",
-        format!("stopping server ({timeout}s before being killed) ...").bright_black()
-    );
+    if countdown > 0 {
+        println!(
+            "{}",
+            format!("stopping server in {countdown}s ({timeout}s before being killed) ...")
+                .bright_black()
+        );
+        println!(
+            "{} {}",
+            "(ctrl+c leaves the countdown running, cancel it with".bright_black(),
+            "mcvcli stop --cancel)".cyan()
+        );
+    } else {
+        println!(
+            "{}",
+            format!("stopping server ({timeout}s before being killed) ...").bright_black()
+        );
+    }
 
-    detached::write_frame(&mut writer, detached::TAG_STOP, &timeout.to_be_bytes()).await?;
+    detached::write_frame(
+        &mut writer,
+        detached::TAG_STOP,
+        &serde_json::to_vec(&detached::StopRequest {
+            timeout,
+            countdown,
+            spec: None,
+        })?,
+    )
+    .await?;
 
     let mut stdout = tokio::io::stdout();
     let mut stderr = tokio::io::stderr();
@@ -99,7 +309,7 @@ pub async fn stop(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
 
     // Wait for the daemon to finish tearing itself down so a follow-up `start` sees a clean slate.
     for _ in 0..50 {
-        if !detached::is_running() {
+        if remote.is_some() || !detached::is_running() {
             break;
         }
 
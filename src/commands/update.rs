@@ -1,4 +1,11 @@
-use crate::{api, config, detached, jar, modpack, profiles};
+use crate::{
+    api,
+    commands::{start::run_hook, webhook},
+    config, detached,
+    hooks::{Environment, Hook},
+    jar, modpack, profiles,
+    webhooks::{Event, Notification},
+};
 
 use clap::ArgMatches;
 use colored::Colorize;
@@ -50,6 +57,8 @@ pub async fn update(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
 
     println!("{}", "checking installed version ...".bright_black());
 
+    // Webhooks belong to the active server, also when another profile is updated.
+    let active = config;
     let mut config = config::Config::new(&format!("{directory}/.mcvcli.json"), false);
     let detected = jar::detect(&directory.clone(), &config).await;
 
@@ -112,7 +121,20 @@ pub async fn update(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
         .interact()?;
 
     let update = items[update];
-    if update == "Update Version" {
+
+    // Like webhooks, the hooks of the active server run, told about the profile that is updated.
+    if !run_hook(
+        Hook::PreUpdate,
+        &active.hooks,
+        &Environment::new(&config),
+        None,
+    )
+    .await
+    {
+        return Ok(1);
+    }
+
+    let updated = if update == "Update Version" {
         let version_index = versions
             .keys()
             .position(|v| {
@@ -185,6 +207,8 @@ pub async fn update(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
             "...".bright_black(),
             "DONE".green().bold()
         );
+
+        format!("{server_version} {}", server_build.name)
     } else if update == "Update Build" {
         let server_version = build
             .version_id
@@ -240,7 +264,9 @@ pub async fn update(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
             "...".bright_black(),
             "DONE".green().bold()
         );
-    } else if update == "Update Modpack" {
+
+        format!("{server_version} {}", server_build.name)
+    } else {
         let modpack = modpack.ok_or_else(|| anyhow::anyhow!("no modpack installed"))?;
         let modpack_versions = modpack_versions
             .iter()
@@ -303,7 +329,35 @@ pub async fn update(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
             "...".bright_black(),
             "DONE".green().bold()
         );
-    }
+
+        format!(
+            "{} {}",
+            modpack.title,
+            modpack_version
+                .name
+                .as_deref()
+                .or(modpack_version.version_number.as_deref())
+                .unwrap_or("unknown")
+        )
+    };
+
+    run_hook(
+        Hook::PostUpdate,
+        &active.hooks,
+        &Environment::new(&config),
+        None,
+    )
+    .await;
+
+    webhook::notify(
+        &active,
+        Notification::new(
+            Event::Update,
+            format!("profile {} updated to {updated}", config.profile_name),
+        )
+        .field("version", updated),
+    )
+    .await;
 
     Ok(0)
 }
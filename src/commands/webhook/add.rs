@@ -0,0 +1,61 @@
+use crate::{
+    config,
+    webhooks::{Event, Format, Webhook},
+};
+
+use clap::ArgMatches;
+use colored::Colorize;
+
+pub async fn add(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
+    let url = matches.get_one::<String>("url").expect("required");
+    let events: Vec<Event> = matches
+        .get_many::<String>("event")
+        .unwrap_or_default()
+        .filter_map(|event| Event::parse(event))
+        .collect();
+    let format = matches
+        .get_one::<String>("format")
+        .and_then(|format| serde_json::from_value::<Format>(format.as_str().into()).ok());
+    let template = matches.get_one::<String>("template");
+    let retries = *matches.get_one::<u32>("retries").expect("required");
+    let backoff = *matches.get_one::<u64>("backoff").expect("required");
+    let mut config = config::Config::new(".mcvcli.json", false);
+
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        println!(
+            "{} {} {}",
+            "invalid url".red(),
+            url.cyan(),
+            "(use http:// or https://)".red()
+        );
+        return Ok(1);
+    }
+
+    println!("{}", "adding webhook ...".bright_black());
+
+    config.webhooks.push(Webhook {
+        url: url.clone(),
+        events,
+        format: format.unwrap_or_else(|| Webhook::detect_format(url)),
+        template: template.cloned(),
+        retries,
+        backoff_secs: backoff,
+    });
+    config.save();
+
+    println!(
+        "{} {}",
+        "adding webhook ...".bright_black(),
+        "DONE".green().bold()
+    );
+
+    println!();
+    println!(
+        "{} {} {}",
+        "use".bright_black(),
+        format!("mcvcli webhook test {}", config.webhooks.len()).cyan(),
+        "to send a test message".bright_black()
+    );
+
+    Ok(0)
+}
@@ -0,0 +1,56 @@
+use crate::config;
+
+use clap::ArgMatches;
+use colored::Colorize;
+
+pub async fn list(_matches: &ArgMatches) -> Result<i32, anyhow::Error> {
+    let config = config::Config::new(".mcvcli.json", false);
+
+    if config.webhooks.is_empty() {
+        println!("{}", "no webhooks configured".red());
+        return Ok(1);
+    }
+
+    for (i, webhook) in config.webhooks.iter().enumerate() {
+        println!();
+        println!(
+            "{} {}",
+            format!("#{}", i + 1).cyan().bold().underline(),
+            webhook.host().cyan().bold()
+        );
+
+        println!("  {} {}", "url:     ".bright_black(), webhook.url.cyan());
+        println!(
+            "  {} {}",
+            "events:  ".bright_black(),
+            match webhook.events.is_empty() {
+                true => "all".to_string(),
+                false => webhook
+                    .events
+                    .iter()
+                    .map(|event| event.name())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            }
+            .cyan()
+        );
+        println!(
+            "  {} {}",
+            "format:  ".bright_black(),
+            serde_json::to_value(webhook.format)?
+                .as_str()
+                .unwrap_or_default()
+                .cyan()
+        );
+        if let Some(template) = &webhook.template {
+            println!("  {} {}", "template:".bright_black(), template.cyan());
+        }
+        println!(
+            "  {} {}",
+            "retries: ".bright_black(),
+            format!("{} (backoff {}s)", webhook.retries, webhook.backoff_secs).cyan()
+        );
+    }
+
+    Ok(0)
+}
@@ -0,0 +1,63 @@
+pub mod add;
+pub mod list;
+pub mod remove;
+pub mod test;
+
+use crate::{
+    config::Config,
+    webhooks::{self, Notification, Webhook},
+};
+
+use colored::Colorize;
+use dialoguer::{FuzzySelect, theme::ColorfulTheme};
+
+/// Sends a notification from a command to the webhooks of the server, warning about the ones
+/// that failed.
+pub async fn notify(config: &Config, notification: Notification) {
+    for (host, err) in webhooks::send(&config.webhooks, &config.profile_name, &notification).await {
+        println!(
+            "{} {}{} {err:#}",
+            "failed to send webhook to".red(),
+            host.cyan(),
+            ":".red()
+        );
+    }
+}
+
+/// The index of the webhook picked by its number from `mcvcli webhook list`, or interactively.
+pub fn select(webhooks: &[Webhook], number: Option<usize>, prompt: &str) -> Option<usize> {
+    if webhooks.is_empty() {
+        println!("{}", "no webhooks configured".red());
+        return None;
+    }
+
+    match number {
+        Some(number) if (1..=webhooks.len()).contains(&number) => Some(number - 1),
+        Some(number) => {
+            println!(
+                "{} {} {}",
+                "webhook".red(),
+                number.to_string().cyan(),
+                "does not exist!".red()
+            );
+            None
+        }
+        None => {
+            let index = FuzzySelect::with_theme(&ColorfulTheme::default())
+                .with_prompt(prompt)
+                .items(
+                    webhooks
+                        .iter()
+                        .map(|webhook| webhook.url.clone())
+                        .collect::<Vec<String>>(),
+                )
+                .default(0)
+                .max_length(5)
+                .interact()
+                .ok()?;
+            println!();
+
+            Some(index)
+        }
+    }
+}
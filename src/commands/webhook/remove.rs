@@ -0,0 +1,35 @@
+use super::select;
+use crate::config;
+
+use clap::ArgMatches;
+use colored::Colorize;
+
+pub async fn remove(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
+    let number = matches.get_one::<usize>("number").copied();
+    let mut config = config::Config::new(".mcvcli.json", false);
+
+    let Some(index) = select(&config.webhooks, number, "Select webhook to remove") else {
+        return Ok(1);
+    };
+
+    let webhook = config.webhooks.remove(index);
+
+    println!(
+        "{} {} {}",
+        "removing webhook to".bright_black(),
+        webhook.host().cyan(),
+        "...".bright_black()
+    );
+
+    config.save();
+
+    println!(
+        "{} {} {} {}",
+        "removing webhook to".bright_black(),
+        webhook.host().cyan(),
+        "...".bright_black(),
+        "DONE".green().bold()
+    );
+
+    Ok(0)
+}
@@ -0,0 +1,46 @@
+use super::select;
+use crate::{
+    config,
+    webhooks::{self, Event, Notification},
+};
+
+use clap::ArgMatches;
+use colored::Colorize;
+
+pub async fn test(matches: &ArgMatches) -> Result<i32, anyhow::Error> {
+    let number = matches.get_one::<usize>("number").copied();
+    let config = config::Config::new(".mcvcli.json", false);
+
+    let Some(index) = select(&config.webhooks, number, "Select webhook to test") else {
+        return Ok(1);
+    };
+    let webhook = &config.webhooks[index];
+
+    println!(
+        "{} {} {}",
+        "sending test message to".bright_black(),
+        webhook.host().cyan(),
+        "...".bright_black()
+    );
+
+    let notification = Notification::new(Event::Test, "this is a test message from mcvcli");
+    if let Err(err) = webhooks::deliver(webhook, &config.profile_name, &notification).await {
+        println!(
+            "{} {}{} {err:#}",
+            "failed to send webhook to".red(),
+            webhook.host().cyan(),
+            ":".red()
+        );
+        return Ok(1);
+    }
+
+    println!(
+        "{} {} {} {}",
+        "sending test message to".bright_black(),
+        webhook.host().cyan(),
+        "...".bright_black(),
+        "DONE".green().bold()
+    );
+
+    Ok(0)
+}
@@ -1,3 +1,5 @@
+use crate::{hooks::Hooks, tls::Tls, webhooks::Webhook};
+
 use colored::Colorize;
 use serde::{Deserialize, Serialize};
 use std::{fs::File, path::Path};
@@ -8,6 +10,55 @@ fn default_stop_command() -> String {
 fn default_detached_log_max_mb() -> u64 {
     10
 }
+fn default_detached_log_retention() -> usize {
+    10
+}
+fn default_detached_log_retention_days() -> u64 {
+    30
+}
+fn default_countdown_message() -> String {
+    "say Server will {action} in {time}".to_string()
+}
+fn default_countdown_cancel_message() -> String {
+    "say Server {action} has been cancelled".to_string()
+}
+fn default_countdown_intervals() -> Vec<u64> {
+    vec![60, 30, 10, 5, 4, 3, 2, 1]
+}
+fn default_countdown_save() -> bool {
+    true
+}
+fn default_restart_max_retries() -> u32 {
+    5
+}
+fn default_restart_backoff_secs() -> u64 {
+    5
+}
+fn default_watchdog_failures() -> u32 {
+    3
+}
+fn default_watchdog_grace_secs() -> u64 {
+    300
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartPolicy {
+    #[default]
+    Never,
+    OnFailure,
+    Always,
+}
+
+impl RestartPolicy {
+    pub fn should_restart(&self, success: bool) -> bool {
+        match self {
+            RestartPolicy::Never => false,
+            RestartPolicy::OnFailure => !success,
+            RestartPolicy::Always => true,
+        }
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -33,6 +84,56 @@ pub struct Config {
 
     #[serde(default = "default_detached_log_max_mb")]
     pub detached_log_max_mb: u64,
+    #[serde(default = "default_detached_log_retention")]
+    pub detached_log_retention: usize,
+    #[serde(default = "default_detached_log_retention_days")]
+    pub detached_log_retention_days: u64,
+
+    #[serde(default)]
+    pub restart_policy: RestartPolicy,
+    #[serde(default = "default_restart_max_retries")]
+    pub restart_max_retries: u32,
+    #[serde(default = "default_restart_backoff_secs")]
+    pub restart_backoff_secs: u64,
+
+    #[serde(default)]
+    pub watchdog_interval_secs: Option<u64>,
+    #[serde(default = "default_watchdog_failures")]
+    pub watchdog_failures: u32,
+    #[serde(default = "default_watchdog_grace_secs")]
+    pub watchdog_grace_secs: u64,
+
+    #[serde(default)]
+    pub cgroup_memory_max_mb: Option<u64>,
+    #[serde(default)]
+    pub cgroup_cpu_max: Option<f64>,
+    #[serde(default)]
+    pub cgroup_pids_max: Option<u64>,
+
+    #[serde(default)]
+    pub metrics_address: Option<String>,
+
+    #[serde(default)]
+    pub remote_address: Option<String>,
+    #[serde(default)]
+    pub remote_token: Option<String>,
+    #[serde(default)]
+    pub remote_tls: Option<Tls>,
+
+    #[serde(default = "default_countdown_message")]
+    pub countdown_message: String,
+    #[serde(default = "default_countdown_cancel_message")]
+    pub countdown_cancel_message: String,
+    #[serde(default = "default_countdown_intervals")]
+    pub countdown_intervals: Vec<u64>,
+    #[serde(default = "default_countdown_save")]
+    pub countdown_save: bool,
+
+    #[serde(default)]
+    pub webhooks: Vec<Webhook>,
+
+    #[serde(default)]
+    pub hooks: Hooks,
 }
 
 impl Config {
@@ -51,6 +152,27 @@ impl Config {
                     extra_flags: Vec::new(),
                     extra_args: Vec::new(),
                     detached_log_max_mb: default_detached_log_max_mb(),
+                    detached_log_retention: default_detached_log_retention(),
+                    detached_log_retention_days: default_detached_log_retention_days(),
+                    restart_policy: RestartPolicy::default(),
+                    restart_max_retries: default_restart_max_retries(),
+                    restart_backoff_secs: default_restart_backoff_secs(),
+                    watchdog_interval_secs: None,
+                    watchdog_failures: default_watchdog_failures(),
+                    watchdog_grace_secs: default_watchdog_grace_secs(),
+                    cgroup_memory_max_mb: None,
+                    cgroup_cpu_max: None,
+                    cgroup_pids_max: None,
+                    metrics_address: None,
+                    remote_address: None,
+                    remote_token: None,
+                    remote_tls: None,
+                    countdown_message: default_countdown_message(),
+                    countdown_cancel_message: default_countdown_cancel_message(),
+                    countdown_intervals: default_countdown_intervals(),
+                    countdown_save: default_countdown_save(),
+                    webhooks: Vec::new(),
+                    hooks: Hooks::default(),
                 };
 
                 let file = File::create(path).expect("failed to create config file");
@@ -88,8 +210,26 @@ impl Config {
         Some(config)
     }
 
+    /// Like [`Config::new_optional`], but a missing or broken file is an error instead of `None`
+    /// or a panic, for callers that must keep running.
+    pub fn load(path: &str) -> Result<Self, anyhow::Error> {
+        let file =
+            File::open(path).map_err(|err| anyhow::anyhow!("failed to open {path}: {err}"))?;
+        let mut config: Config = serde_json::from_reader(file)
+            .map_err(|err| anyhow::anyhow!("failed to parse {path}: {err}"))?;
+
+        config.path = path.to_string();
+
+        Ok(config)
+    }
+
     pub fn save(&self) {
-        let file = File::create(&self.path).expect("failed to create config file");
+        // With remote control set up the config holds its token, so only the owner may read it.
+        let file = match self.remote_token {
+            Some(_) => crate::detached::create_private(Path::new(&self.path)),
+            None => File::create(&self.path),
+        }
+        .expect("failed to create config file");
         serde_json::to_writer_pretty(file, &self).expect("failed to write config file");
     }
 }
@@ -0,0 +1,307 @@
+use crate::logs::{self, Event};
+
+use rustyline::{
+    Context, Editor, ExternalPrinter, Helper, completion::Completer, error::ReadlineError,
+    highlight::Highlighter, hint::Hinter, history::FileHistory, validate::Validator,
+};
+use std::{
+    collections::BTreeSet,
+    io::{IsTerminal, Write},
+    path::PathBuf,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+use tokio::sync::mpsc;
+
+const COMMANDS: &[&str] = &[
+    "advancement",
+    "ban",
+    "ban-ip",
+    "banlist",
+    "clear",
+    "deop",
+    "difficulty",
+    "effect",
+    "enchant",
+    "execute",
+    "experience",
+    "gamemode",
+    "gamerule",
+    "give",
+    "help",
+    "kick",
+    "kill",
+    "list",
+    "locate",
+    "msg",
+    "op",
+    "pardon",
+    "pardon-ip",
+    "reload",
+    "save-all",
+    "save-off",
+    "save-on",
+    "say",
+    "scoreboard",
+    "seed",
+    "setblock",
+    "setworldspawn",
+    "spawnpoint",
+    "stop",
+    "summon",
+    "teleport",
+    "tell",
+    "tellraw",
+    "time",
+    "title",
+    "tp",
+    "weather",
+    "whitelist",
+    "worldborder",
+    "xp",
+];
+
+fn arguments(command: &str) -> &'static [&'static str] {
+    match command {
+        "whitelist" => &["add", "remove", "list", "on", "off", "reload"],
+        "gamemode" => &["survival", "creative", "adventure", "spectator"],
+        "difficulty" => &["peaceful", "easy", "normal", "hard"],
+        "time" => &["set", "add", "query", "day", "night", "noon", "midnight"],
+        "weather" => &["clear", "rain", "thunder"],
+        "save-all" => &["flush"],
+        "experience" | "xp" => &["add", "set", "query"],
+        "effect" => &["give", "clear"],
+        _ => &[],
+    }
+}
+
+/// Completes console commands, their common arguments and the names of online players.
+struct ConsoleHelper {
+    players: Arc<Mutex<BTreeSet<String>>>,
+}
+
+impl Completer for ConsoleHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let before = &line[..pos];
+        let start = before.rfind(' ').map_or(0, |space| space + 1);
+        let word = before[start..].to_lowercase();
+        let previous: Vec<&str> = before[..start].split_whitespace().collect();
+
+        let mut candidates: Vec<String> = match previous.first() {
+            None => COMMANDS.iter().map(|command| command.to_string()).collect(),
+            Some(command) => arguments(command.trim_start_matches('/'))
+                .iter()
+                .map(|argument| argument.to_string())
+                .chain(self.players.lock().unwrap().iter().cloned())
+                .collect(),
+        };
+        candidates.retain(|candidate| candidate.to_lowercase().starts_with(&word));
+
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for ConsoleHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ConsoleHelper {}
+
+impl Validator for ConsoleHelper {}
+
+impl Helper for ConsoleHelper {}
+
+pub enum Input {
+    Line(String),
+    /// Ctrl-C, which no longer raises a signal while the line editor owns the terminal.
+    Interrupted,
+    Closed,
+}
+
+/// A line editor for the server console. Input stays on its own line below the server output,
+/// with a per-server history and tab completion of commands and online players.
+pub struct Console {
+    printer: Mutex<Box<dyn ExternalPrinter + Send>>,
+    active: Arc<AtomicBool>,
+    pending: Mutex<Vec<u8>>,
+    players: Arc<Mutex<BTreeSet<String>>>,
+    line: Mutex<Vec<u8>>,
+
+    #[cfg(unix)]
+    termios: Option<libc::termios>,
+}
+
+impl Console {
+    /// Starts reading lines on a separate thread, keeping the history in the given file. Returns
+    /// `None` when stdin or stdout is not a terminal, in which case input should be forwarded as is.
+    pub fn start(history: Option<PathBuf>) -> Option<(Self, mpsc::UnboundedReceiver<Input>)> {
+        if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+            return None;
+        }
+
+        #[cfg(unix)]
+        let termios = {
+            let mut termios = std::mem::MaybeUninit::<libc::termios>::uninit();
+            // SAFETY: tcgetattr only writes to the provided termios struct.
+            unsafe {
+                (libc::tcgetattr(libc::STDIN_FILENO, termios.as_mut_ptr()) == 0)
+                    .then(|| termios.assume_init())
+            }
+        };
+
+        let config = rustyline::Config::builder()
+            .max_history_size(1000)
+            .ok()?
+            .history_ignore_dups(true)
+            .ok()?
+            .auto_add_history(true)
+            .build();
+        let mut editor = Editor::<ConsoleHelper, FileHistory>::with_config(config).ok()?;
+
+        let players = Arc::new(Mutex::new(BTreeSet::new()));
+        editor.set_helper(Some(ConsoleHelper {
+            players: Arc::clone(&players),
+        }));
+
+        if let Some(history) = &history {
+            if let Some(parent) = history.parent() {
+                let _ = std::fs::create_dir_all(parent);
+            }
+            let _ = editor.load_history(history);
+        }
+
+        let printer = editor.create_external_printer().ok()?;
+        let active = Arc::new(AtomicBool::new(true));
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        {
+            let active = Arc::clone(&active);
+            std::thread::spawn(move || {
+                loop {
+                    let input = match editor.readline("> ") {
+                        Ok(line) => {
+                            if let Some(history) = &history {
+                                let _ = editor.save_history(history);
+                            }
+
+                            Input::Line(line)
+                        }
+                        Err(ReadlineError::Interrupted) => Input::Interrupted,
+                        Err(_) => Input::Closed,
+                    };
+
+                    let done = !matches!(input, Input::Line(_));
+                    if tx.send(input).is_err() || done {
+                        break;
+                    }
+                }
+
+                active.store(false, Ordering::SeqCst);
+            });
+        }
+
+        Some((
+            Console {
+                printer: Mutex::new(Box::new(printer)),
+                active,
+                pending: Mutex::new(Vec::new()),
+                players,
+                line: Mutex::new(Vec::new()),
+                #[cfg(unix)]
+                termios,
+            },
+            rx,
+        ))
+    }
+
+    /// Prints server output above the prompt. While the editor is reading, only complete lines
+    /// are printed, so a partial line never ends up next to the prompt.
+    pub fn print(&self, data: &[u8]) {
+        let mut pending = self.pending.lock().unwrap();
+        pending.extend_from_slice(data);
+
+        if !self.active.load(Ordering::SeqCst) {
+            let mut stdout = std::io::stdout();
+            let _ = stdout.write_all(&pending);
+            let _ = stdout.flush();
+            pending.clear();
+            return;
+        }
+
+        if let Some(position) = pending.iter().rposition(|byte| *byte == b'\n') {
+            let lines: Vec<u8> = pending.drain(..=position).collect();
+            let _ = self
+                .printer
+                .lock()
+                .unwrap()
+                .print(String::from_utf8_lossy(&lines).into_owned());
+        }
+    }
+
+    /// Keeps the online players used for completion up to date.
+    pub fn event(&self, event: &Event) {
+        let mut players = self.players.lock().unwrap();
+
+        match event {
+            Event::Join { player } => {
+                players.insert(player.clone());
+            }
+            Event::Leave { player } => {
+                players.remove(player);
+            }
+            _ => {}
+        }
+    }
+
+    /// Like [`Console::event`], for raw console output.
+    pub fn parse(&self, data: &[u8]) {
+        let mut line = self.line.lock().unwrap();
+        line.extend_from_slice(data);
+
+        while let Some(position) = line.iter().position(|byte| *byte == b'\n') {
+            let complete: Vec<u8> = line.drain(..=position).collect();
+            if let Some(event) = logs::event(&String::from_utf8_lossy(&complete)) {
+                self.event(&event);
+            }
+        }
+
+        // A server printing without newlines shouldn't grow the buffer forever.
+        if line.len() > 64 * 1024 {
+            line.clear();
+        }
+    }
+
+    /// Like [`Console::print`], for messages of our own.
+    pub fn notice(&self, message: impl std::fmt::Display) {
+        self.print(format!("{message}\n").as_bytes());
+    }
+
+    pub fn set_players(&self, players: Vec<String>) {
+        *self.players.lock().unwrap() = players.into_iter().collect();
+    }
+}
+
+impl Drop for Console {
+    /// The reading thread may still be blocked in the editor, so the terminal settings it changed
+    /// are put back by hand.
+    fn drop(&mut self) {
+        let _ = std::io::stdout().write_all(&self.pending.lock().unwrap());
+
+        #[cfg(unix)]
+        if let Some(termios) = self.termios {
+            // SAFETY: termios was filled in by tcgetattr for the same file descriptor.
+            unsafe {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios);
+            }
+        }
+    }
+}
@@ -0,0 +1,283 @@
+use crate::{detached, logs};
+
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{BufRead, Write},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// The amount of exits kept in the history file.
+const MAX_EXITS: usize = 100;
+/// The amount of stack frames kept in a summary.
+const MAX_FRAMES: usize = 5;
+
+pub const REPORTS_DIR: &str = "crash-reports";
+
+/// What could be found out about why the server crashed, from a crash report or the console.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Summary {
+    /// The file name of the report in `crash-reports/`, if the server wrote one.
+    pub report: Option<String>,
+    pub description: Option<String>,
+    pub exception: Option<String>,
+    #[serde(default)]
+    pub suspected_mods: Vec<String>,
+    #[serde(default)]
+    pub frames: Vec<String>,
+}
+
+impl Summary {
+    /// The summary as labelled values in the order they are shown, without the stack frames.
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = Vec::new();
+
+        if let Some(report) = &self.report {
+            fields.push(("report", format!("{REPORTS_DIR}/{report}")));
+        }
+        if let Some(description) = &self.description {
+            fields.push(("description", description.clone()));
+        }
+        if let Some(exception) = &self.exception {
+            fields.push(("exception", exception.clone()));
+        }
+        if !self.suspected_mods.is_empty() {
+            fields.push(("suspected", self.suspected_mods.join(", ")));
+        }
+
+        fields
+    }
+}
+
+/// A server exit as recorded in the history file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Exit {
+    pub time: i64,
+    pub code: i32,
+    pub uptime_secs: u64,
+    pub crash: Option<Summary>,
+}
+
+/// A crash to browse with `mcvcli crash`, from the exit history or a report that was written
+/// while the server was not supervised by mcvcli.
+#[derive(Debug, Clone)]
+pub struct Crash {
+    pub time: i64,
+    pub code: Option<i32>,
+    pub summary: Summary,
+}
+
+/// Parses a Minecraft (or Forge/NeoForge) crash report.
+pub fn parse_report(content: &str) -> Summary {
+    let mut summary = Summary::default();
+    let mut lines = content.lines().peekable();
+
+    while let Some(line) = lines.next() {
+        if let Some(description) = line.strip_prefix("Description: ") {
+            summary.description = Some(description.trim().to_string());
+
+            // The exception and its stack trace follow the description after a blank line.
+            while lines.peek().is_some_and(|line| line.trim().is_empty()) {
+                lines.next();
+            }
+            summary.exception = lines.next().map(|line| line.trim().to_string());
+
+            while let Some(frame) = lines
+                .peek()
+                .and_then(|line| line.trim().strip_prefix("at "))
+            {
+                if summary.frames.len() < MAX_FRAMES {
+                    summary.frames.push(frame.to_string());
+                }
+                lines.next();
+            }
+        } else if let Some(mods) = line
+            .trim()
+            .strip_prefix("Suspected Mods:")
+            .or_else(|| line.trim().strip_prefix("Suspected Mod:"))
+        {
+            let mods = mods.trim();
+            if !mods.is_empty() && mods != "NONE" {
+                summary.suspected_mods.push(mods.to_string());
+            }
+
+            // Forge lists one mod per line, with its details indented further.
+            while let Some(line) = lines.peek() {
+                if !line.starts_with('\t') && !line.starts_with(' ') {
+                    break;
+                }
+
+                if !line.starts_with("\t\t") && !line.trim().is_empty() {
+                    summary.suspected_mods.push(line.trim().to_string());
+                }
+                lines.next();
+            }
+        }
+    }
+
+    summary
+}
+
+/// Finds the last exception in console output. Without a stack trace, the last line mentioning
+/// an error is used, which covers the JVM failing to start.
+pub fn parse_output(output: &str) -> Summary {
+    let lines: Vec<String> = output.lines().map(logs::strip_ansi).collect();
+    let mut summary = Summary::default();
+
+    let is_frame = |line: &str| line.trim_start().starts_with("at ");
+    let start = (1..lines.len())
+        .rev()
+        .find(|&i| is_frame(&lines[i]) && !is_frame(&lines[i - 1]));
+
+    match start {
+        Some(start) => {
+            summary.exception = Some(lines[start - 1].trim().to_string());
+            summary.frames = lines[start..]
+                .iter()
+                .take_while(|line| is_frame(line))
+                .take(MAX_FRAMES)
+                .map(|line| line.trim().trim_start_matches("at ").to_string())
+                .collect();
+        }
+        None => {
+            summary.exception = lines
+                .iter()
+                .rev()
+                .find(|line| {
+                    let line = line.to_lowercase();
+                    line.contains("error") || line.contains("exception")
+                })
+                .map(|line| line.trim().to_string());
+        }
+    }
+
+    summary
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).ok()?.modified().ok()
+}
+
+/// Crash reports in `crash-reports/`, newest first.
+pub fn reports() -> Vec<PathBuf> {
+    let mut reports: Vec<(SystemTime, PathBuf)> = std::fs::read_dir(REPORTS_DIR)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|extension| extension == "txt"))
+                .filter_map(|path| Some((modified(&path)?, path)))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    reports.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    reports.into_iter().map(|(_, path)| path).collect()
+}
+
+/// The newest crash report written since `started`, if the server wrote one.
+pub fn report_since(started: SystemTime) -> Option<PathBuf> {
+    reports()
+        .into_iter()
+        .find(|path| modified(path).is_some_and(|modified| modified >= started))
+}
+
+/// Works out why the server that started at `started` crashed, preferring a crash report it
+/// wrote over the console output.
+pub fn detect(started: SystemTime, output: &str) -> Summary {
+    if let Some(path) = report_since(started)
+        && let Ok(content) = std::fs::read_to_string(&path)
+    {
+        let mut summary = parse_report(&content);
+        summary.report = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string());
+
+        return summary;
+    }
+
+    parse_output(output)
+}
+
+/// The server's own `logs/latest.log`, if it was written after `started`.
+pub fn server_log_since(started: SystemTime) -> String {
+    let path = Path::new("logs").join("latest.log");
+    if modified(&path).is_none_or(|modified| modified < started) {
+        return String::new();
+    }
+
+    let content = std::fs::read_to_string(path).unwrap_or_default();
+    let lines: Vec<&str> = content.lines().collect();
+
+    lines[lines.len().saturating_sub(500)..].join("\n")
+}
+
+/// Exits recorded in the history file, oldest first.
+pub fn history() -> Vec<Exit> {
+    let Ok(file) = std::fs::File::open(detached::exits_path()) else {
+        return Vec::new();
+    };
+
+    std::io::BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<Exit>(&line).ok())
+        .collect()
+}
+
+/// Appends an exit to the history file, keeping the last `MAX_EXITS`.
+pub fn record(exit: &Exit) -> Result<(), anyhow::Error> {
+    std::fs::create_dir_all(detached::dir())?;
+
+    let mut exits = history();
+    exits.push(exit.clone());
+    let skip = exits.len().saturating_sub(MAX_EXITS);
+
+    let mut file = std::fs::File::create(detached::exits_path())?;
+    for exit in &exits[skip..] {
+        writeln!(file, "{}", serde_json::to_string(exit)?)?;
+    }
+
+    Ok(())
+}
+
+/// Crashes from the exit history together with crash reports it doesn't know about, newest
+/// first.
+pub fn crashes() -> Vec<Crash> {
+    let mut crashes: Vec<Crash> = history()
+        .into_iter()
+        .filter_map(|exit| {
+            Some(Crash {
+                time: exit.time,
+                code: Some(exit.code),
+                summary: exit.crash?,
+            })
+        })
+        .collect();
+
+    for path in reports() {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string());
+        if crashes.iter().any(|crash| crash.summary.report == name) {
+            continue;
+        }
+
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let mut summary = parse_report(&content);
+        summary.report = name;
+
+        crashes.push(Crash {
+            time: modified(&path)
+                .map(|modified| chrono::DateTime::<chrono::Utc>::from(modified).timestamp())
+                .unwrap_or(0),
+            code: None,
+            summary,
+        });
+    }
+
+    crashes.sort_by_key(|crash| std::cmp::Reverse(crash.time));
+    crashes
+}
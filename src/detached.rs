@@ -1,20 +1,44 @@
+use crate::{
+    cgroup::Limits,
+    config::{Config, RestartPolicy},
+    hooks::Hooks,
+    tls::Tls,
+    webhooks::Webhook,
+};
 use interprocess::local_socket::{
     GenericNamespaced, ToNsName,
     tokio::{Stream, prelude::*},
 };
+
 use serde::{Deserialize, Serialize};
+use sha2::Digest;
 use std::path::{Path, PathBuf};
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 
 pub const DIR: &str = ".mcvcli.detached";
 
 pub const TAG_STDIN: u8 = 1;
 pub const TAG_STOP: u8 = 2;
 pub const TAG_ATTACH: u8 = 3;
+pub const TAG_EXEC: u8 = 4;
+pub const TAG_FOLLOW: u8 = 5;
+pub const TAG_STATUS: u8 = 6;
+pub const TAG_CANCEL: u8 = 7;
+pub const TAG_RESTART_SERVER: u8 = 8;
+pub const TAG_AUTH: u8 = 9;
 
 pub const TAG_STDOUT: u8 = 10;
 pub const TAG_STDERR: u8 = 11;
 pub const TAG_EXIT: u8 = 12;
+pub const TAG_RESTART: u8 = 13;
+pub const TAG_EXEC_RESULT: u8 = 14;
+pub const TAG_REPLAY_END: u8 = 15;
+pub const TAG_STATUS_RESULT: u8 = 16;
+pub const TAG_EVENT: u8 = 17;
+pub const TAG_CANCEL_RESULT: u8 = 18;
+pub const TAG_CHALLENGE: u8 = 19;
+pub const TAG_AUTH_RESULT: u8 = 20;
+pub const TAG_CRASH: u8 = 21;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct State {
@@ -23,7 +47,7 @@ pub struct State {
     pub java_pid: u32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Spec {
     pub binary: String,
     pub java_home: String,
@@ -33,8 +57,241 @@ pub struct Spec {
     pub extra_args: Vec<String>,
     pub stop_command: String,
     pub log_max_bytes: u64,
+    pub log_retention: usize,
+    pub log_retention_days: u64,
+    pub restart_policy: RestartPolicy,
+    pub restart_max_retries: u32,
+    pub restart_backoff_secs: u64,
+    pub limits: Limits,
+    pub metrics_address: Option<String>,
+    #[serde(default)]
+    pub remote_address: Option<String>,
+    #[serde(default)]
+    pub remote_token: Option<String>,
+    #[serde(default)]
+    pub remote_tls: Option<Tls>,
+    #[serde(default)]
+    pub watchdog: Option<Watchdog>,
+    pub countdown: Countdown,
+    #[serde(default)]
+    pub webhooks: Vec<Webhook>,
+    #[serde(default)]
+    pub java_version: u8,
+    #[serde(default)]
+    pub hooks: Hooks,
+}
+
+impl Spec {
+    pub fn new(config: &Config, binary: String, java_home: String) -> Self {
+        Spec {
+            binary,
+            java_home,
+            jar_file: config.jar_file.clone(),
+            ram_mb: config.ram_mb,
+            extra_flags: config.extra_flags.clone(),
+            extra_args: config.extra_args.clone(),
+            stop_command: config.stop_command.clone(),
+            log_max_bytes: config.detached_log_max_mb.saturating_mul(1024 * 1024),
+            log_retention: config.detached_log_retention,
+            log_retention_days: config.detached_log_retention_days,
+            restart_policy: config.restart_policy,
+            restart_max_retries: config.restart_max_retries,
+            restart_backoff_secs: config.restart_backoff_secs,
+            limits: Limits {
+                memory_max_mb: config.cgroup_memory_max_mb,
+                cpu_max: config.cgroup_cpu_max,
+                pids_max: config.cgroup_pids_max,
+            },
+            metrics_address: config.metrics_address.clone(),
+            remote_address: config.remote_address.clone(),
+            remote_token: config.remote_token.clone(),
+            remote_tls: config.remote_tls.clone(),
+            watchdog: config.watchdog_interval_secs.map(|interval_secs| Watchdog {
+                interval_secs,
+                failures: config.watchdog_failures,
+                grace_secs: config.watchdog_grace_secs,
+            }),
+            countdown: Countdown {
+                message: config.countdown_message.clone(),
+                cancel_message: config.countdown_cancel_message.clone(),
+                intervals: config.countdown_intervals.clone(),
+                save: config.countdown_save,
+            },
+            webhooks: config.webhooks.clone(),
+            java_version: config.java_version,
+            hooks: config.hooks.clone(),
+        }
+    }
+}
+
+/// Restarts a server that stopped answering pings, see `commands::daemon::watchdog`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Watchdog {
+    pub interval_secs: u64,
+    pub failures: u32,
+    pub grace_secs: u64,
+}
+
+/// In-game warnings sent before a countdown stop or restart. In the messages, `{action}` is
+/// replaced with `stop` or `restart` and `{time}` with the time remaining.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Countdown {
+    pub message: String,
+    pub cancel_message: String,
+    pub intervals: Vec<u64>,
+    pub save: bool,
+}
+
+/// Payload of a `TAG_STOP` or `TAG_RESTART_SERVER` frame. With a `countdown` (seconds), the daemon
+/// warns players and stops or restarts the server once it runs out, which a `TAG_CANCEL` frame
+/// aborts. A restart can carry the `spec` to respawn the server with, which the daemon only
+/// takes over once the restart goes ahead, so a cancelled one leaves the current spec in place.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StopRequest {
+    pub timeout: u64,
+    #[serde(default)]
+    pub countdown: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spec: Option<Spec>,
+}
+
+/// Payload of a `TAG_RESTART` frame, sent to attached clients when the supervisor is about to
+/// respawn the server, either after it exited on its own or because a restart was `requested`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RestartNotice {
+    pub code: i32,
+    pub attempt: u32,
+    pub max_retries: u32,
+    pub delay_secs: u64,
+    #[serde(default)]
+    pub requested: bool,
+}
+
+/// Payload of a `TAG_EXEC` frame. The daemon answers with a single `TAG_EXEC_RESULT` frame holding
+/// the console output captured after the command was sent.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExecRequest {
+    pub command: String,
+    pub quiet_ms: u64,
+    pub timeout_ms: u64,
+    #[serde(default)]
+    pub identity: Option<Identity>,
+    /// Collect output until one of these appears (or the timeout), instead of until the server
+    /// goes quiet.
+    #[serde(default)]
+    pub until: Vec<String>,
+}
+
+/// Who is behind a client connection, sent as the `TAG_ATTACH` payload (and with `exec`
+/// requests) so the daemon can record who typed what in the audit log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Identity {
+    pub user: String,
+    pub host: String,
+    pub label: Option<String>,
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+/// The name of the user this process runs as, falling back to the environment.
+pub fn current_user() -> Option<String> {
+    let pid = sysinfo::get_current_pid().ok()?;
+    let mut system = sysinfo::System::new();
+    system.refresh_processes_specifics(
+        sysinfo::ProcessesToUpdate::Some(&[pid]),
+        true,
+        sysinfo::ProcessRefreshKind::nothing().with_user(sysinfo::UpdateKind::Always),
+    );
+
+    let users = sysinfo::Users::new_with_refreshed_list();
+    system
+        .process(pid)
+        .and_then(|process| process.user_id())
+        .and_then(|uid| users.get_user_by_id(uid))
+        .map(|user| user.name().to_string())
+        .or_else(|| {
+            std::env::var("USER")
+                .or_else(|_| std::env::var("USERNAME"))
+                .ok()
+        })
+}
+
+impl Identity {
+    pub fn current(label: Option<String>, read_only: bool) -> Self {
+        Identity {
+            user: current_user().unwrap_or_else(|| "unknown".to_string()),
+            host: sysinfo::System::host_name().unwrap_or_else(|| "unknown".to_string()),
+            label,
+            read_only,
+        }
+    }
+}
+
+impl std::fmt::Display for Identity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}@{}", self.user, self.host)?;
+
+        if let Some(label) = &self.label {
+            write!(f, " ({label})")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Payload of a `TAG_STATUS_RESULT` frame, what the daemon knows about the running server from
+/// its console output.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DaemonStatus {
+    pub ready_at: Option<i64>,
+    pub players: Vec<String>,
+    #[serde(default)]
+    pub process: Option<ProcessStatus>,
+}
+
+/// The java process as seen by the daemon, for clients that can't look at it themselves.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProcessStatus {
+    pub pid: u32,
+    pub memory: u64,
+    pub start_time: u64,
+}
+
+/// A daemon reached over TCP instead of the local socket, see `mcvcli config --remote-address`.
+/// Connections start with the daemon sending a `TAG_CHALLENGE` frame with random bytes, which the
+/// client answers with a `TAG_AUTH` frame holding [`auth_response`], so the token itself is never
+/// sent. The daemon replies with a `TAG_AUTH_RESULT` frame (1 or 0) and closes the connection if
+/// the response was wrong. With `tls`, all of this happens inside a mutual TLS session; without
+/// it the token is never sent, but the console traffic after it is not encrypted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Remote {
+    pub address: String,
+    pub token: String,
+    #[serde(default)]
+    pub tls: Option<Tls>,
+}
+
+/// The length of an [`auth_response`], the only frame the daemon reads before authentication.
+pub const AUTH_RESPONSE_LEN: usize = 32;
+
+pub fn auth_response(challenge: &[u8], token: &str) -> Vec<u8> {
+    let mut hasher = sha2::Sha256::new();
+    hasher.update(challenge);
+    hasher.update(token.as_bytes());
+
+    hasher.finalize().to_vec()
+}
+
+/// Compares in constant time, so response times don't leak how much of a secret was right.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
 }
 
+/// A connection to a daemon, either over the local socket or over TCP.
+pub trait Connection: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Connection for T {}
+
 pub fn dir() -> &'static Path {
     Path::new(DIR)
 }
@@ -51,8 +308,76 @@ pub fn log_path() -> PathBuf {
     dir().join("latest.log")
 }
 
-pub fn log_old_path() -> PathBuf {
-    dir().join("latest.log.old")
+pub fn log_archive_dir() -> PathBuf {
+    dir().join("logs")
+}
+
+/// Rotated console logs (`logs/YYYY-MM-DD-HHMMSS.log.gz`), oldest first. A plain `.log` is an
+/// archive that is still being (or failed to be) compressed.
+pub fn log_archives() -> Vec<PathBuf> {
+    let mut archives: Vec<PathBuf> = std::fs::read_dir(log_archive_dir())
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    let name = path.to_string_lossy();
+                    name.ends_with(".log.gz") || name.ends_with(".log")
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // Archives rotated within the same second get a `-N` suffix, which has to sort after the
+    // plain name.
+    archives.sort_by_key(|path| {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let name = name.trim_end_matches(".gz").trim_end_matches(".log");
+
+        match name.split_at_checked(17) {
+            Some((stamp, suffix)) => (
+                stamp.to_string(),
+                suffix.trim_start_matches('-').parse::<u32>().unwrap_or(0),
+            ),
+            None => (name.to_string(), 0),
+        }
+    });
+
+    archives
+}
+
+pub fn schedule_path() -> PathBuf {
+    dir().join("schedule.json")
+}
+
+pub fn history_path() -> PathBuf {
+    dir().join("history")
+}
+
+pub fn audit_path() -> PathBuf {
+    dir().join("audit.log")
+}
+
+pub fn usage_path() -> PathBuf {
+    dir().join("usage.jsonl")
+}
+
+/// Past server exits, with a summary of the crash for the ones that failed.
+pub fn exits_path() -> PathBuf {
+    dir().join("exits.jsonl")
+}
+
+/// The pid of the daemon, for service managers that track a forking service by pid file.
+pub fn pid_path() -> PathBuf {
+    dir().join("daemon.pid")
+}
+
+/// The FIFO the server reads its console input from, so it outlives the daemon.
+pub fn stdin_path() -> PathBuf {
+    dir().join("stdin")
 }
 
 pub fn socket_label(identifier: &str) -> String {
@@ -68,6 +393,7 @@ pub fn write_state(state: &State) -> Result<(), anyhow::Error> {
     std::fs::create_dir_all(dir())?;
     let file = std::fs::File::create(state_path())?;
     serde_json::to_writer_pretty(file, state)?;
+    std::fs::write(pid_path(), format!("{}\n", state.daemon_pid))?;
 
     Ok(())
 }
@@ -78,17 +404,48 @@ pub fn read_spec() -> Result<Spec, anyhow::Error> {
     Ok(serde_json::from_reader(file)?)
 }
 
+/// Creates (or truncates) a file that only its owner can read, for files holding tokens. The
+/// permissions are set before anything is written, also for a file that already existed.
+pub fn create_private(path: &Path) -> std::io::Result<std::fs::File> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        options.mode(0o600);
+    }
+    let file = options.open(path)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        // `mode` only applies to new files, not to one left behind by an older version.
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+
+    Ok(file)
+}
+
 pub fn write_spec(spec: &Spec) -> Result<(), anyhow::Error> {
     std::fs::create_dir_all(dir())?;
-    let file = std::fs::File::create(spec_path())?;
+
+    // The spec holds the remote token.
+    let file = create_private(&spec_path())?;
     serde_json::to_writer_pretty(file, spec)?;
 
     Ok(())
 }
 
 pub fn cleanup() {
+    if let Some(state) = read_state() {
+        crate::registry::unregister(&state.identifier);
+    }
+
     let _ = std::fs::remove_file(state_path());
     let _ = std::fs::remove_file(spec_path());
+    let _ = std::fs::remove_file(stdin_path());
+    let _ = std::fs::remove_file(pid_path());
 }
 
 pub fn is_running() -> bool {
@@ -101,8 +458,10 @@ pub fn is_running() -> bool {
     process_alive(&sys, state.daemon_pid, "mcvcli") || process_alive(&sys, state.java_pid, "java")
 }
 
-fn process_alive(sys: &sysinfo::System, pid: u32, name_hint: &str) -> bool {
+pub fn process_alive(sys: &sysinfo::System, pid: u32, name_hint: &str) -> bool {
     match sys.process(sysinfo::Pid::from(pid as usize)) {
+        // A killed daemon can linger as a zombie until something reaps it.
+        Some(process) if process.status() == sysinfo::ProcessStatus::Zombie => false,
         Some(process) => process
             .exe()
             .and_then(|exe| exe.file_stem())
@@ -113,6 +472,54 @@ fn process_alive(sys: &sysinfo::System, pid: u32, name_hint: &str) -> bool {
     }
 }
 
+/// The java pid of a server whose daemon is gone, if that process is still alive and still runs
+/// in this directory, so a reused pid is never mistaken for the server.
+pub fn orphan() -> Option<u32> {
+    let state = read_state()?;
+    let sys = sysinfo::System::new_all();
+
+    if process_alive(&sys, state.daemon_pid, "mcvcli")
+        || !process_alive(&sys, state.java_pid, "java")
+    {
+        return None;
+    }
+
+    let cwd = sys
+        .process(sysinfo::Pid::from(state.java_pid as usize))?
+        .cwd()?
+        .canonicalize()
+        .ok()?;
+
+    (cwd == std::env::current_dir().ok()?.canonicalize().ok()?).then_some(state.java_pid)
+}
+
+/// Asks the daemon for the state it tracks from the console (see `DaemonStatus`).
+pub async fn status(remote: Option<&Remote>) -> Result<DaemonStatus, anyhow::Error> {
+    let connection = connect_to(remote).await?;
+    let (mut reader, mut writer) = tokio::io::split(connection);
+
+    write_frame(&mut writer, TAG_STATUS, &[]).await?;
+
+    loop {
+        if let (TAG_STATUS_RESULT, payload) = read_frame(&mut reader).await? {
+            return Ok(serde_json::from_slice(&payload)?);
+        }
+    }
+}
+
+/// Runs a console command on the local daemon and returns the output it collected.
+pub async fn exec(request: &ExecRequest) -> Result<Vec<u8>, anyhow::Error> {
+    let (mut reader, mut writer) = tokio::io::split(connect().await?);
+
+    write_frame(&mut writer, TAG_EXEC, &serde_json::to_vec(request)?).await?;
+
+    loop {
+        if let (TAG_EXEC_RESULT, output) = read_frame(&mut reader).await? {
+            return Ok(output);
+        }
+    }
+}
+
 pub async fn connect() -> Result<Stream, anyhow::Error> {
     let state = read_state().ok_or_else(|| anyhow::anyhow!("server is not running"))?;
     let label = socket_label(&state.identifier);
@@ -121,13 +528,74 @@ pub async fn connect() -> Result<Stream, anyhow::Error> {
     Ok(Stream::connect(name).await?)
 }
 
-pub fn spawn_daemon() -> Result<u32, anyhow::Error> {
+/// Connects to a remote daemon and authenticates with its token.
+pub async fn connect_remote(remote: &Remote) -> Result<Box<dyn Connection>, anyhow::Error> {
+    let timeout = std::time::Duration::from_secs(10);
+    let stream = tokio::time::timeout(timeout, tokio::net::TcpStream::connect(&remote.address))
+        .await
+        .map_err(|_| anyhow::anyhow!("timed out connecting to {}", remote.address))??;
+
+    let mut stream: Box<dyn Connection> = match &remote.tls {
+        Some(tls) => {
+            let (connector, name) = tls.connector(&remote.address)?;
+            let stream = tokio::time::timeout(timeout, connector.connect(name, stream))
+                .await
+                .map_err(|_| anyhow::anyhow!("timed out connecting to {}", remote.address))?
+                .map_err(|err| {
+                    anyhow::anyhow!("failed to set up TLS with {}: {err}", remote.address)
+                })?;
+
+            Box::new(stream)
+        }
+        None => Box::new(stream),
+    };
+
+    let authenticated = tokio::time::timeout(timeout, async {
+        let (tag, challenge) = read_frame(&mut stream).await?;
+        if tag != TAG_CHALLENGE {
+            return Err(anyhow::anyhow!("{} is not a mcvcli daemon", remote.address));
+        }
+
+        write_frame(
+            &mut stream,
+            TAG_AUTH,
+            &auth_response(&challenge, &remote.token),
+        )
+        .await?;
+
+        let (tag, result) = read_frame(&mut stream).await?;
+        Ok(tag == TAG_AUTH_RESULT && result.first() == Some(&1))
+    })
+    .await
+    .map_err(|_| anyhow::anyhow!("timed out authenticating with {}", remote.address))??;
+
+    if !authenticated {
+        return Err(anyhow::anyhow!("wrong remote token for {}", remote.address));
+    }
+
+    Ok(stream)
+}
+
+/// Connects to the daemon of the server in the current directory, or to a remote one.
+pub async fn connect_to(remote: Option<&Remote>) -> Result<Box<dyn Connection>, anyhow::Error> {
+    Ok(match remote {
+        Some(remote) => connect_remote(remote).await?,
+        None => Box::new(connect().await?),
+    })
+}
+
+/// Spawns the supervisor in the background. With `adopt`, it takes over that already running
+/// java process instead of starting one.
+pub fn spawn_daemon(adopt: Option<u32>) -> Result<u32, anyhow::Error> {
     use std::process::{Command, Stdio};
 
     let exe = std::env::current_exe()?;
     let mut command = Command::new(exe);
+    command.arg("daemon");
+    if let Some(pid) = adopt {
+        command.arg("--adopt").arg(pid.to_string());
+    }
     command
-        .arg("daemon")
         .stdin(Stdio::null())
         .stdout(Stdio::null())
         .stderr(Stdio::null());
@@ -173,9 +641,27 @@ pub async fn write_frame<W: AsyncWriteExt + Unpin>(
     Ok(())
 }
 
+/// The largest payload [`read_frame`] accepts, so a peer can't make it allocate gigabytes.
+pub const MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
 pub async fn read_frame<R: AsyncReadExt + Unpin>(reader: &mut R) -> std::io::Result<(u8, Vec<u8>)> {
+    read_frame_limited(reader, MAX_FRAME_LEN).await
+}
+
+/// Reads a frame, failing without reading its payload if it is longer than `max_len`.
+pub async fn read_frame_limited<R: AsyncReadExt + Unpin>(
+    reader: &mut R,
+    max_len: usize,
+) -> std::io::Result<(u8, Vec<u8>)> {
     let tag = reader.read_u8().await?;
     let len = reader.read_u32().await? as usize;
+    if len > max_len {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("frame of {len} bytes is larger than {max_len}"),
+        ));
+    }
+
     let mut payload = vec![0u8; len];
     reader.read_exact(&mut payload).await?;
 
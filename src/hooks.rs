@@ -0,0 +1,259 @@
+use crate::config::Config;
+
+use serde::{Deserialize, Serialize};
+use std::{process::Stdio, time::Duration};
+
+fn default_timeout_secs() -> u64 {
+    300
+}
+
+/// Shell commands run around the lifecycle of the server and around updates, backups and
+/// restores, in order. Each one gets the `MCVCLI_*` variables from [`Environment`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hooks {
+    /// Before the server starts, a failing command aborts the start.
+    #[serde(default, alias = "pre_start")]
+    pub pre_start: Vec<String>,
+    /// Once the server has finished loading.
+    #[serde(default, alias = "post_start")]
+    pub post_start: Vec<String>,
+    /// Before mcvcli stops or restarts the server.
+    #[serde(default, alias = "pre_stop")]
+    pub pre_stop: Vec<String>,
+    /// After the server has exited, for whatever reason.
+    #[serde(default, alias = "post_stop")]
+    pub post_stop: Vec<String>,
+    /// After the server has crashed, before `postStop`.
+    #[serde(default, alias = "on_crash")]
+    pub on_crash: Vec<String>,
+    /// Before `mcvcli update` changes the jar or modpack, a failing command aborts the update.
+    #[serde(default, alias = "pre_update")]
+    pub pre_update: Vec<String>,
+    /// After an update has been installed.
+    #[serde(default, alias = "post_update")]
+    pub post_update: Vec<String>,
+    /// Before a backup is created, a failing command aborts the backup.
+    #[serde(default, alias = "pre_backup")]
+    pub pre_backup: Vec<String>,
+    /// After a backup has been created.
+    #[serde(default, alias = "post_backup")]
+    pub post_backup: Vec<String>,
+    /// Before a backup is restored, a failing command aborts the restore.
+    #[serde(default, alias = "pre_restore")]
+    pub pre_restore: Vec<String>,
+    /// After a backup has been restored.
+    #[serde(default, alias = "post_restore")]
+    pub post_restore: Vec<String>,
+    /// How long a single command may run before it is killed and counts as failed.
+    #[serde(default = "default_timeout_secs", alias = "timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+impl Default for Hooks {
+    fn default() -> Self {
+        Hooks {
+            pre_start: Vec::new(),
+            post_start: Vec::new(),
+            pre_stop: Vec::new(),
+            post_stop: Vec::new(),
+            on_crash: Vec::new(),
+            pre_update: Vec::new(),
+            post_update: Vec::new(),
+            pre_backup: Vec::new(),
+            post_backup: Vec::new(),
+            pre_restore: Vec::new(),
+            post_restore: Vec::new(),
+            timeout_secs: default_timeout_secs(),
+        }
+    }
+}
+
+impl Hooks {
+    pub fn commands(&self, hook: Hook) -> &[String] {
+        match hook {
+            Hook::PreStart => &self.pre_start,
+            Hook::PostStart => &self.post_start,
+            Hook::PreStop => &self.pre_stop,
+            Hook::PostStop => &self.post_stop,
+            Hook::OnCrash => &self.on_crash,
+            Hook::PreUpdate => &self.pre_update,
+            Hook::PostUpdate => &self.post_update,
+            Hook::PreBackup => &self.pre_backup,
+            Hook::PostBackup => &self.post_backup,
+            Hook::PreRestore => &self.pre_restore,
+            Hook::PostRestore => &self.post_restore,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hook {
+    PreStart,
+    PostStart,
+    PreStop,
+    PostStop,
+    OnCrash,
+    PreUpdate,
+    PostUpdate,
+    PreBackup,
+    PostBackup,
+    PreRestore,
+    PostRestore,
+}
+
+impl Hook {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Hook::PreStart => "pre-start",
+            Hook::PostStart => "post-start",
+            Hook::PreStop => "pre-stop",
+            Hook::PostStop => "post-stop",
+            Hook::OnCrash => "on-crash",
+            Hook::PreUpdate => "pre-update",
+            Hook::PostUpdate => "post-update",
+            Hook::PreBackup => "pre-backup",
+            Hook::PostBackup => "post-backup",
+            Hook::PreRestore => "pre-restore",
+            Hook::PostRestore => "post-restore",
+        }
+    }
+}
+
+/// What a hook is told about the server.
+#[derive(Debug, Clone, Default)]
+pub struct Environment {
+    pub profile: String,
+    pub jar_file: String,
+    pub java_version: u8,
+    pub pid: Option<u32>,
+    pub exit_code: Option<i32>,
+    /// The exception from the crash summary, for `onCrash`.
+    pub exception: Option<String>,
+    /// The backup being created or restored.
+    pub backup: Option<String>,
+}
+
+impl Environment {
+    pub fn new(config: &Config) -> Self {
+        Environment {
+            profile: config.profile_name.clone(),
+            jar_file: config.jar_file.clone(),
+            java_version: config.java_version,
+            ..Default::default()
+        }
+    }
+
+    fn variables(&self, hook: Hook) -> Vec<(&'static str, String)> {
+        let mut variables = vec![
+            ("MCVCLI_HOOK", hook.name().to_string()),
+            ("MCVCLI_PROFILE", self.profile.clone()),
+            ("MCVCLI_JAR", self.jar_file.clone()),
+            ("MCVCLI_JAVA_VERSION", self.java_version.to_string()),
+        ];
+        if let Ok(directory) = std::env::current_dir() {
+            variables.push(("MCVCLI_DIRECTORY", directory.to_string_lossy().to_string()));
+        }
+        if let Some(pid) = self.pid {
+            variables.push(("MCVCLI_PID", pid.to_string()));
+        }
+        if let Some(code) = self.exit_code {
+            variables.push(("MCVCLI_EXIT_CODE", code.to_string()));
+        }
+        if let Some(exception) = &self.exception {
+            variables.push(("MCVCLI_CRASH_EXCEPTION", exception.clone()));
+        }
+        if let Some(backup) = &self.backup {
+            variables.push(("MCVCLI_BACKUP", backup.clone()));
+        }
+
+        variables
+    }
+}
+
+/// The combined output of the commands that ran, and the first one that failed.
+pub struct Outcome {
+    pub output: Vec<u8>,
+    pub result: Result<(), anyhow::Error>,
+}
+
+fn shell(command: &str) -> tokio::process::Command {
+    #[cfg(unix)]
+    {
+        let mut shell = tokio::process::Command::new("sh");
+        shell.arg("-c").arg(command);
+        shell
+    }
+    #[cfg(not(unix))]
+    {
+        let mut shell = tokio::process::Command::new("cmd");
+        shell.arg("/C").arg(command);
+        shell
+    }
+}
+
+/// Runs the commands of a hook in the server directory, stopping at the first one that fails.
+pub async fn run(hook: Hook, hooks: &Hooks, environment: &Environment) -> Outcome {
+    let mut output = Vec::new();
+
+    for command in hooks.commands(hook) {
+        let child = shell(command)
+            .envs(environment.variables(hook))
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn();
+        let child = match child {
+            Ok(child) => child,
+            Err(err) => {
+                return Outcome {
+                    output,
+                    result: Err(anyhow::anyhow!("failed to run `{command}`: {err}")),
+                };
+            }
+        };
+
+        let result = match tokio::time::timeout(
+            Duration::from_secs(hooks.timeout_secs),
+            child.wait_with_output(),
+        )
+        .await
+        {
+            Ok(Ok(result)) => result,
+            Ok(Err(err)) => {
+                return Outcome {
+                    output,
+                    result: Err(anyhow::anyhow!("failed to run `{command}`: {err}")),
+                };
+            }
+            Err(_) => {
+                return Outcome {
+                    output,
+                    result: Err(anyhow::anyhow!(
+                        "`{command}` did not finish within {}s",
+                        hooks.timeout_secs
+                    )),
+                };
+            }
+        };
+
+        output.extend_from_slice(&result.stdout);
+        output.extend_from_slice(&result.stderr);
+
+        if !result.status.success() {
+            return Outcome {
+                output,
+                result: Err(match result.status.code() {
+                    Some(code) => anyhow::anyhow!("`{command}` exited with code {code}"),
+                    None => anyhow::anyhow!("`{command}` was killed"),
+                }),
+            };
+        }
+    }
+
+    Outcome {
+        output,
+        result: Ok(()),
+    }
+}
@@ -0,0 +1,282 @@
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeDelta};
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+    Fatal,
+}
+
+impl Level {
+    pub fn parse(level: &str) -> Option<Self> {
+        match level.to_ascii_uppercase().as_str() {
+            "TRACE" => Some(Level::Trace),
+            "DEBUG" => Some(Level::Debug),
+            "INFO" => Some(Level::Info),
+            "WARN" | "WARNING" => Some(Level::Warn),
+            "ERROR" | "SEVERE" => Some(Level::Error),
+            "FATAL" => Some(Level::Fatal),
+            _ => None,
+        }
+    }
+}
+
+/// Parses a relative duration like `30s`, `15m`, `2h` or `7d`.
+pub fn parse_duration(duration: &str) -> Option<TimeDelta> {
+    let unit = duration.chars().last()?;
+    let amount = duration[..duration.len() - unit.len_utf8()]
+        .parse::<i64>()
+        .ok()?;
+
+    match unit {
+        's' => TimeDelta::try_seconds(amount),
+        'm' => TimeDelta::try_minutes(amount),
+        'h' => TimeDelta::try_hours(amount),
+        'd' => TimeDelta::try_days(amount),
+        _ => None,
+    }
+}
+
+/// The header of a server log line, e.g. `[12:34:56] [Server thread/INFO]: message` (vanilla,
+/// Fabric), `[12:34:56 INFO]: message` (Paper) or
+/// `[18Oct2026 12:34:56.789] [Server thread/INFO] [minecraft/DedicatedServer]: message` (Forge).
+#[derive(Debug)]
+pub struct Entry<'a> {
+    pub time: NaiveTime,
+    pub level: Option<Level>,
+    pub message: &'a str,
+}
+
+fn parse_time(token: &str) -> Option<NaiveTime> {
+    let token = token.split('.').next()?;
+
+    NaiveTime::parse_from_str(token, "%H:%M:%S").ok()
+}
+
+/// Parses a log line, returning `None` for lines without a header (stack traces, wrapped output).
+pub fn parse(line: &str) -> Option<Entry<'_>> {
+    let (header, mut rest) = line.trim_start().strip_prefix('[')?.split_once(']')?;
+
+    let mut time = None;
+    let mut level = None;
+    for token in header.split_whitespace() {
+        if time.is_none()
+            && let Some(parsed) = parse_time(token)
+        {
+            time = Some(parsed);
+        } else if let Some(parsed) = Level::parse(token) {
+            level = Some(parsed);
+        }
+    }
+
+    if level.is_none()
+        && let Some((group, after)) = rest
+            .trim_start()
+            .strip_prefix('[')
+            .and_then(|group| group.split_once(']'))
+        && let Some((_, parsed)) = group.rsplit_once('/')
+    {
+        level = Level::parse(parsed);
+        rest = after;
+    }
+
+    // Forge adds the logger name as another group before the separator.
+    if let Some((_, after)) = rest
+        .trim_start()
+        .strip_prefix('[')
+        .and_then(|group| group.split_once(']'))
+    {
+        rest = after;
+    }
+
+    let rest = rest.trim_start();
+    let message = rest.strip_prefix(':').unwrap_or(rest).trim_start();
+
+    Some(Entry {
+        time: time?,
+        level,
+        message,
+    })
+}
+
+/// Something that happened on the server, recognised from its console output. Sent to clients in
+/// `TAG_EVENT` frames.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    Ready { startup_secs: f64 },
+    Join { player: String },
+    Leave { player: String },
+    Chat { player: String, message: String },
+    Watchdog { message: String },
+    Crash { message: String },
+}
+
+/// Removes ANSI escape sequences, which some server software writes even when not on a terminal.
+pub fn strip_ansi(line: &str) -> String {
+    let mut stripped = String::with_capacity(line.len());
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            if chars.next() == Some('[') {
+                for c in chars.by_ref() {
+                    if c.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+            }
+        } else {
+            stripped.push(c);
+        }
+    }
+
+    stripped
+}
+
+/// Java edition names are 1-16 word characters. Bedrock players joining through Geyser/Floodgate
+/// get a prefix (usually `.`) and may contain spaces, which is accepted as well.
+fn is_player_name(name: &str) -> bool {
+    let name = name.strip_prefix(['.', '*']).unwrap_or(name);
+
+    !name.is_empty()
+        && name.len() <= 32
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ' ')
+}
+
+pub fn event(line: &str) -> Option<Event> {
+    let line = strip_ansi(line);
+    let entry = parse(&line)?;
+    let message = entry.message.trim_end();
+
+    if let Some(rest) = message.strip_prefix("Done (")
+        && let Some((seconds, _)) = rest.split_once("s)!")
+    {
+        return Some(Event::Ready {
+            startup_secs: seconds.parse().unwrap_or(0.0),
+        });
+    }
+
+    if let Some(player) = message.strip_suffix(" joined the game")
+        && is_player_name(player)
+    {
+        return Some(Event::Join {
+            player: player.to_string(),
+        });
+    }
+
+    if let Some(player) = message.strip_suffix(" left the game")
+        && is_player_name(player)
+    {
+        return Some(Event::Leave {
+            player: player.to_string(),
+        });
+    }
+
+    let chat = message.strip_prefix("[Not Secure] ").unwrap_or(message);
+    if let Some((player, text)) = chat
+        .strip_prefix('<')
+        .and_then(|chat| chat.split_once("> "))
+        && is_player_name(player)
+    {
+        return Some(Event::Chat {
+            player: player.to_string(),
+            message: text.to_string(),
+        });
+    }
+
+    if message.contains("The server has stopped responding!")
+        || message.starts_with("A single server tick took")
+    {
+        return Some(Event::Watchdog {
+            message: message.to_string(),
+        });
+    }
+
+    if message.starts_with("This crash report has been saved to")
+        || message.starts_with("Encountered an unexpected exception")
+    {
+        return Some(Event::Crash {
+            message: message.to_string(),
+        });
+    }
+
+    None
+}
+
+/// The log files of the server itself (`logs/*.log.gz` and `logs/latest.log`), oldest first.
+pub fn server_files() -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir("logs")
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.to_string_lossy().ends_with(".log.gz"))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // Archives are named `YYYY-MM-DD-N.log.gz`, so sort by date and then numerically by `N`.
+    files.sort_by_key(|path| {
+        let name = path
+            .file_name()
+            .map(|name| {
+                name.to_string_lossy()
+                    .trim_end_matches(".log.gz")
+                    .to_string()
+            })
+            .unwrap_or_default();
+
+        match name.rsplit_once('-') {
+            Some((date, index)) => (date.to_string(), index.parse::<u32>().unwrap_or(0)),
+            None => (name, 0),
+        }
+    });
+
+    let latest = Path::new("logs").join("latest.log");
+    if latest.exists() {
+        files.push(latest);
+    }
+
+    files
+}
+
+pub fn read(path: &Path) -> std::io::Result<String> {
+    let file = std::fs::File::open(path)?;
+    let mut bytes = Vec::new();
+
+    if path.extension().is_some_and(|extension| extension == "gz") {
+        GzDecoder::new(file).read_to_end(&mut bytes)?;
+    } else {
+        std::io::BufReader::new(file).read_to_end(&mut bytes)?;
+    }
+
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// The date of the last line in a log file: the date in the name of a server archive, or the
+/// modification date of anything else.
+pub fn end_date(path: &Path) -> NaiveDate {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.get(..10))
+        .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+        .or_else(|| {
+            path.metadata()
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .map(|modified| DateTime::<Local>::from(modified).date_naive())
+        })
+        .unwrap_or_else(|| Local::now().date_naive())
+}
@@ -1,19 +1,81 @@
 mod api;
 mod backups;
+mod cgroup;
 mod commands;
 mod config;
+mod console;
+mod crash;
 mod detached;
+mod hooks;
 mod jar;
 mod java;
+mod logs;
 mod modpack;
+mod ping;
 mod profiles;
 mod progress;
+mod properties;
+mod rcon;
+mod registry;
+mod remotes;
+mod schedule;
+mod service;
+mod tls;
+mod usage;
+mod webhooks;
 
 use clap::{Arg, Command};
 use colored::Colorize;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// `--remote` and `--token`, for commands that can also talk to a daemon on another machine.
+fn remote_args() -> [Arg; 2] {
+    [
+        Arg::new("remote")
+            .long("remote")
+            .help("Control a server on another machine, by saved remote name or host:port")
+            .num_args(1)
+            .required(false),
+        Arg::new("token")
+            .long("token")
+            .help("The token of the remote server (defaults to the saved remote or MCVCLI_REMOTE_TOKEN)")
+            .num_args(1)
+            .requires("remote")
+            .required(false),
+    ]
+}
+
+/// Selects the service for `mcvcli service` subcommands.
+fn service_args() -> [Arg; 4] {
+    [
+        Arg::new("init")
+            .long("init")
+            .help("The init system to write the service for (detected by default)")
+            .num_args(1)
+            .value_parser(["systemd", "openrc"])
+            .required(false),
+        Arg::new("system")
+            .long("system")
+            .help("Use a systemd system unit instead of a user unit (the default for root)")
+            .num_args(0)
+            .default_value("false")
+            .value_parser(clap::value_parser!(bool))
+            .required(false),
+        Arg::new("name")
+            .long("name")
+            .short('n')
+            .help("The name of the service (defaults to mcvcli-<directory name>)")
+            .num_args(1)
+            .required(false),
+        Arg::new("unit_dir")
+            .long("unit-dir")
+            .help("Write the service to this directory instead of the init system's, without enabling it")
+            .num_args(1)
+            .required(false),
+    ]
+}
+
 fn cli() -> Command {
     Command::new("mcvcli")
         .about("A simple CLI for interacting with Minecraft servers")
@@ -132,6 +194,120 @@ fn cli() -> Command {
                         .num_args(1)
                         .required(false),
                 )
+                .arg(
+                    Arg::new("restart_policy")
+                        .long("restart-policy")
+                        .help("When the detached server should be restarted after exiting")
+                        .num_args(1)
+                        .value_parser(["never", "on-failure", "always"])
+                        .required(false),
+                )
+                .arg(
+                    Arg::new("restart_max_retries")
+                        .long("restart-max-retries")
+                        .help("The maximum amount of consecutive restarts before giving up")
+                        .num_args(1)
+                        .value_parser(clap::value_parser!(u32))
+                        .required(false),
+                )
+                .arg(
+                    Arg::new("restart_backoff")
+                        .long("restart-backoff")
+                        .help("The initial delay between restarts, doubled after each attempt (seconds)")
+                        .num_args(1)
+                        .value_parser(clap::value_parser!(u64).range(1..))
+                        .required(false),
+                )
+                .arg(
+                    Arg::new("watchdog_interval")
+                        .long("watchdog-interval")
+                        .help("How often the detached server is pinged to restart it when it hangs, 0 to disable (seconds)")
+                        .num_args(1)
+                        .value_parser(clap::value_parser!(u64))
+                        .required(false),
+                )
+                .arg(
+                    Arg::new("watchdog_failures")
+                        .long("watchdog-failures")
+                        .help("The amount of failed pings in a row before the watchdog restarts the server")
+                        .num_args(1)
+                        .value_parser(clap::value_parser!(u32).range(1..))
+                        .required(false),
+                )
+                .arg(
+                    Arg::new("watchdog_grace")
+                        .long("watchdog-grace")
+                        .help("How long the watchdog leaves a booting server alone, unless it is done loading earlier (seconds)")
+                        .num_args(1)
+                        .value_parser(clap::value_parser!(u64))
+                        .required(false),
+                )
+                .arg(
+                    Arg::new("memory_limit")
+                        .long("memory-limit")
+                        .help("The cgroup memory limit of the detached server on Linux, 0 to remove (MB)")
+                        .num_args(1)
+                        .value_parser(clap::value_parser!(u64))
+                        .required(false),
+                )
+                .arg(
+                    Arg::new("cpu_limit")
+                        .long("cpu-limit")
+                        .help("The cgroup cpu limit of the detached server on Linux, 0 to remove (cores)")
+                        .num_args(1)
+                        .value_parser(clap::value_parser!(f64))
+                        .required(false),
+                )
+                .arg(
+                    Arg::new("pids_limit")
+                        .long("pids-limit")
+                        .help("The cgroup process/thread limit of the detached server on Linux, 0 to remove")
+                        .num_args(1)
+                        .value_parser(clap::value_parser!(u64))
+                        .required(false),
+                )
+                .arg(
+                    Arg::new("metrics_address")
+                        .long("metrics-address")
+                        .help("The address the daemon serves Prometheus metrics on (e.g. 127.0.0.1:9225), empty to disable")
+                        .num_args(1)
+                        .required(false),
+                )
+                .arg(
+                    Arg::new("remote_address")
+                        .long("remote-address")
+                        .help("The address the daemon accepts remote control connections on (e.g. 0.0.0.0:25580), empty to disable")
+                        .num_args(1)
+                        .required(false),
+                )
+                .arg(
+                    Arg::new("remote_token")
+                        .long("remote-token")
+                        .help("The token remote clients authenticate with, empty to generate a new one")
+                        .num_args(1)
+                        .required(false),
+                )
+                .arg(
+                    Arg::new("remote_tls_cert")
+                        .long("remote-tls-cert")
+                        .help("The certificate (PEM) the daemon presents to remote clients, enables mutual TLS, empty to disable")
+                        .num_args(1)
+                        .required(false),
+                )
+                .arg(
+                    Arg::new("remote_tls_key")
+                        .long("remote-tls-key")
+                        .help("The private key (PEM) of the remote TLS certificate")
+                        .num_args(1)
+                        .required(false),
+                )
+                .arg(
+                    Arg::new("remote_tls_ca")
+                        .long("remote-tls-ca")
+                        .help("The CA certificate (PEM) remote client certificates must be signed by")
+                        .num_args(1)
+                        .required(false),
+                )
                 .arg_required_else_help(false),
         )
         .subcommand(
@@ -214,6 +390,26 @@ fn cli() -> Command {
                         .value_parser(clap::value_parser!(u64).range(1..))
                         .required(false),
                 )
+                .arg(
+                    Arg::new("wait")
+                        .long("wait")
+                        .short('w')
+                        .help("Wait until the detached server has finished loading")
+                        .num_args(0)
+                        .default_value("false")
+                        .value_parser(clap::value_parser!(bool))
+                        .requires("detached")
+                        .required(false),
+                )
+                .arg(
+                    Arg::new("wait_timeout")
+                        .long("wait-timeout")
+                        .help("The amount of time to wait for the server to finish loading (seconds)")
+                        .num_args(1)
+                        .default_value("300")
+                        .value_parser(clap::value_parser!(u64).range(1..))
+                        .required(false),
+                )
                 .arg_required_else_help(false),
         )
         .subcommand(
@@ -229,16 +425,208 @@ fn cli() -> Command {
                         .value_parser(clap::value_parser!(u64).range(1..))
                         .required(false),
                 )
+                .arg(
+                    Arg::new("all")
+                        .long("all")
+                        .short('a')
+                        .help("Stop all running servers on this machine")
+                        .num_args(0)
+                        .default_value("false")
+                        .value_parser(clap::value_parser!(bool))
+                        .required(false),
+                )
+                .arg(
+                    Arg::new("countdown")
+                        .long("countdown")
+                        .short('c')
+                        .help("Warn players in-game for this long before stopping (seconds)")
+                        .num_args(1)
+                        .default_value("0")
+                        .value_parser(clap::value_parser!(u64))
+                        .required(false),
+                )
+                .arg(
+                    Arg::new("cancel")
+                        .long("cancel")
+                        .help("Cancel a running stop or restart countdown")
+                        .num_args(0)
+                        .default_value("false")
+                        .value_parser(clap::value_parser!(bool))
+                        .conflicts_with_all(["countdown", "all"])
+                        .required(false),
+                )
+                .args(remote_args())
+                .mut_arg("remote", |arg| arg.conflicts_with("all"))
                 .arg_required_else_help(false),
         )
         .subcommand(
             Command::new("attach")
                 .about("Attaches to the Minecraft server console")
+                .arg(
+                    Arg::new("read_only")
+                        .long("read-only")
+                        .short('r')
+                        .help("Only watch the console, without being able to send commands")
+                        .num_args(0)
+                        .default_value("false")
+                        .value_parser(clap::value_parser!(bool))
+                        .required(false),
+                )
+                .arg(
+                    Arg::new("label")
+                        .long("label")
+                        .short('l')
+                        .help("A name to record in the audit log next to your user and host")
+                        .num_args(1)
+                        .required(false),
+                )
+                .args(remote_args())
                 .arg_required_else_help(false),
         )
+        .subcommand(
+            Command::new("exec")
+                .about("Runs a console command on the Minecraft server and prints its output")
+                .arg(
+                    Arg::new("command")
+                        .help("The console command to run (e.g., `whitelist add Steve`)")
+                        .num_args(1)
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("quiet")
+                        .long("quiet-period")
+                        .short('q')
+                        .help("How long the server has to stay silent before the output is considered complete (milliseconds)")
+                        .num_args(1)
+                        .default_value("500")
+                        .value_parser(clap::value_parser!(u64).range(1..))
+                        .required(false),
+                )
+                .arg(
+                    Arg::new("timeout")
+                        .long("timeout")
+                        .short('t')
+                        .help("The maximum amount of time to wait for output (seconds)")
+                        .num_args(1)
+                        .default_value("5")
+                        .value_parser(clap::value_parser!(u64).range(1..))
+                        .required(false),
+                )
+                .args(remote_args())
+                .arg_required_else_help(true),
+        )
         .subcommand(
             Command::new("status")
                 .about("Gets the status of the Minecraft server (when detached)")
+                .arg(
+                    Arg::new("watch")
+                        .long("watch")
+                        .short('w')
+                        .help("Keep updating the resource usage of the server")
+                        .num_args(0)
+                        .default_value("false")
+                        .value_parser(clap::value_parser!(bool))
+                        .required(false),
+                )
+                .arg(
+                    Arg::new("history")
+                        .long("history")
+                        .help("Summarize the recorded resource usage over a period (e.g. 1h, 24h, 7d)")
+                        .num_args(1)
+                        .conflicts_with("watch")
+                        .required(false),
+                )
+                .args(remote_args())
+                .mut_arg("remote", |arg| arg.conflicts_with_all(["watch", "history"]))
+                .arg_required_else_help(false),
+        )
+        .subcommand(
+            Command::new("logs")
+                .about("Shows the console log of the Minecraft server")
+                .arg(
+                    Arg::new("tail")
+                        .long("tail")
+                        .short('n')
+                        .help("Only show the last N lines")
+                        .num_args(1)
+                        .value_parser(clap::value_parser!(usize))
+                        .required(false),
+                )
+                .arg(
+                    Arg::new("follow")
+                        .long("follow")
+                        .short('f')
+                        .help("Keep printing new lines as they are written")
+                        .num_args(0)
+                        .default_value("false")
+                        .value_parser(clap::value_parser!(bool))
+                        .required(false),
+                )
+                .arg(
+                    Arg::new("since")
+                        .long("since")
+                        .short('s')
+                        .help("Only show lines since a time (e.g., `30m`, `2h`, `14:30` or `2024-01-31 14:30`)")
+                        .num_args(1)
+                        .required(false),
+                )
+                .arg(
+                    Arg::new("level")
+                        .long("level")
+                        .short('l')
+                        .help("Only show lines of this level or above")
+                        .num_args(1)
+                        .value_parser(["TRACE", "DEBUG", "INFO", "WARN", "ERROR", "FATAL"])
+                        .ignore_case(true)
+                        .required(false),
+                )
+                .arg(
+                    Arg::new("grep")
+                        .long("grep")
+                        .short('g')
+                        .help("Only show lines matching a regular expression")
+                        .num_args(1)
+                        .required(false),
+                )
+                .arg(
+                    Arg::new("history")
+                        .long("history")
+                        .help("Read the server's own log history (logs/*.log.gz and logs/latest.log)")
+                        .num_args(0)
+                        .default_value("false")
+                        .value_parser(clap::value_parser!(bool))
+                        .required(false),
+                )
+                .arg_required_else_help(false),
+        )
+        .subcommand(
+            Command::new("restart")
+                .about("Restarts the detached Minecraft server, applying config changes")
+                .arg(
+                    Arg::new("timeout")
+                        .long("timeout")
+                        .short('t')
+                        .help("The amount of time to wait for the server to stop (seconds)")
+                        .num_args(1)
+                        .default_value("20")
+                        .value_parser(clap::value_parser!(u64).range(1..))
+                        .required(false),
+                )
+                .arg(
+                    Arg::new("countdown")
+                        .long("countdown")
+                        .short('c')
+                        .help("Warn players in-game for this long before restarting (seconds)")
+                        .num_args(1)
+                        .default_value("0")
+                        .value_parser(clap::value_parser!(u64))
+                        .required(false),
+                )
+                .arg_required_else_help(false),
+        )
+        .subcommand(
+            Command::new("ps")
+                .about("Lists all running Minecraft servers on this machine")
                 .arg_required_else_help(false),
         )
         .subcommand(
@@ -272,6 +660,67 @@ fn cli() -> Command {
                         .required(false),
                 )
         )
+        .subcommand(
+            Command::new("rcon")
+                .about("Runs console commands on a Minecraft server over RCON")
+                .arg(
+                    Arg::new("command")
+                        .help("The console command to run, opens an interactive console when omitted")
+                        .num_args(1)
+                        .required(false),
+                )
+                .arg(
+                    Arg::new("address")
+                        .long("address")
+                        .short('a')
+                        .help("The RCON address of the server (defaults to the rcon.port from server.properties)")
+                        .num_args(1)
+                        .required(false),
+                )
+                .arg(
+                    Arg::new("password")
+                        .long("password")
+                        .short('p')
+                        .help("The RCON password (defaults to the rcon.password from server.properties)")
+                        .num_args(1)
+                        .required(false),
+                )
+                .arg(
+                    Arg::new("timeout")
+                        .long("timeout")
+                        .short('t')
+                        .help("The maximum amount of time to wait for the server (seconds)")
+                        .num_args(1)
+                        .default_value("5")
+                        .value_parser(clap::value_parser!(u64).range(1..))
+                        .required(false),
+                )
+        )
+        .subcommand(
+            Command::new("recover")
+                .about("Takes over a detached server whose supervisor died, so it can be controlled again")
+        )
+        .subcommand(
+            Command::new("serve")
+                .about("Serves a JSON/WebSocket management API for the server")
+                .arg(
+                    Arg::new("address")
+                        .long("address")
+                        .short('a')
+                        .help("The address to listen on")
+                        .num_args(1)
+                        .default_value("127.0.0.1:25590")
+                        .required(false),
+                )
+                .arg(
+                    Arg::new("token")
+                        .long("token")
+                        .short('t')
+                        .help("The token clients have to send (defaults to MCVCLI_SERVE_TOKEN, or a generated one)")
+                        .num_args(1)
+                        .required(false),
+                )
+        )
         .subcommand(
             Command::new("version")
                 .about("Gets the installed version of the Minecraft server")
@@ -403,6 +852,318 @@ fn cli() -> Command {
                 .arg_required_else_help(true)
                 .subcommand_required(true),
         )
+        .subcommand(
+            Command::new("crash")
+                .about("Browses past server crashes and their crash reports")
+                .subcommand(
+                    Command::new("list")
+                        .about("Lists past crashes, newest first")
+                        .arg_required_else_help(false),
+                )
+                .subcommand(
+                    Command::new("show")
+                        .about("Shows the summary of a crash")
+                        .arg(
+                            Arg::new("number")
+                                .help("The number of the crash from `mcvcli crash list`")
+                                .num_args(1)
+                                .default_value("1")
+                                .value_parser(clap::value_parser!(usize))
+                                .required(false),
+                        )
+                        .arg(
+                            Arg::new("full")
+                                .long("full")
+                                .short('f')
+                                .help("Print the whole crash report")
+                                .num_args(0)
+                                .default_value("false")
+                                .value_parser(clap::value_parser!(bool))
+                                .required(false),
+                        )
+                        .arg_required_else_help(false),
+                )
+                .arg_required_else_help(true)
+                .subcommand_required(true),
+        )
+        .subcommand(
+            Command::new("schedule")
+                .about("Manages jobs run by the detached server on a schedule")
+                .subcommand(
+                    Command::new("add")
+                        .about("Adds a new scheduled job")
+                        .arg(
+                            Arg::new("expression")
+                                .help("The cron expression to run the job on (e.g., `0 4 * * *` or `@daily`)")
+                                .num_args(1)
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::new("action")
+                                .help("The action to run")
+                                .num_args(1)
+                                .value_parser(["command", "restart", "backup"])
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::new("argument")
+                                .help("The console command to send (command) or the backup format (backup)")
+                                .num_args(1)
+                                .required(false),
+                        )
+                        .arg(
+                            Arg::new("timeout")
+                                .long("timeout")
+                                .short('t')
+                                .help("The amount of time to wait for the server to stop when restarting (seconds)")
+                                .num_args(1)
+                                .default_value("20")
+                                .value_parser(clap::value_parser!(u64).range(1..))
+                                .required(false),
+                        )
+                        .arg(
+                            Arg::new("countdown")
+                                .long("countdown")
+                                .short('c')
+                                .help("Warn players in-game for this long before restarting (seconds)")
+                                .num_args(1)
+                                .default_value("0")
+                                .value_parser(clap::value_parser!(u64))
+                                .required(false),
+                        )
+                        .arg_required_else_help(true),
+                )
+                .subcommand(
+                    Command::new("list")
+                        .about("Lists all scheduled jobs")
+                        .arg_required_else_help(false),
+                )
+                .subcommand(
+                    Command::new("remove")
+                        .about("Removes a scheduled job")
+                        .arg(
+                            Arg::new("id")
+                                .help("The id of the scheduled job to remove")
+                                .num_args(1)
+                                .required(false),
+                        )
+                        .arg_required_else_help(false),
+                )
+                .arg_required_else_help(true)
+                .subcommand_required(true),
+        )
+        .subcommand(
+            Command::new("service")
+                .about("Manages a systemd or openrc service that starts the server at boot")
+                .subcommand(
+                    Command::new("install")
+                        .about("Writes and enables a service for the server")
+                        .args(service_args())
+                        .arg(
+                            Arg::new("restart")
+                                .long("restart")
+                                .help("When systemd restarts the detached server after it exited on its own")
+                                .num_args(1)
+                                .default_value("on-failure")
+                                .value_parser(["no", "on-failure", "always"])
+                                .required(false),
+                        )
+                        .arg(
+                            Arg::new("env")
+                                .long("env")
+                                .short('e')
+                                .help("An environment variable for the service (KEY=VALUE, can be repeated)")
+                                .num_args(1)
+                                .action(clap::ArgAction::Append)
+                                .required(false),
+                        )
+                        .arg(
+                            Arg::new("no_enable")
+                                .long("no-enable")
+                                .help("Only write the service, without enabling it")
+                                .num_args(0)
+                                .default_value("false")
+                                .value_parser(clap::value_parser!(bool))
+                                .required(false),
+                        )
+                        .arg_required_else_help(false),
+                )
+                .subcommand(
+                    Command::new("uninstall")
+                        .about("Disables and removes the service of the server")
+                        .args(service_args())
+                        .arg_required_else_help(false),
+                )
+                .subcommand(
+                    Command::new("status")
+                        .about("Shows whether the service is installed, enabled and running")
+                        .args(service_args())
+                        .arg_required_else_help(false),
+                )
+                .arg_required_else_help(true)
+                .subcommand_required(true),
+        )
+        .subcommand(
+            Command::new("webhook")
+                .about("Manages webhooks notified about server events")
+                .subcommand(
+                    Command::new("add")
+                        .about("Adds a webhook")
+                        .arg(
+                            Arg::new("url")
+                                .help("The url to post to (e.g., a Discord or Slack webhook url)")
+                                .num_args(1)
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::new("event")
+                                .long("event")
+                                .short('e')
+                                .help("An event to send (can be repeated, defaults to all events)")
+                                .num_args(1)
+                                .action(clap::ArgAction::Append)
+                                .value_parser(webhooks::Event::ALL)
+                                .required(false),
+                        )
+                        .arg(
+                            Arg::new("format")
+                                .long("format")
+                                .short('f')
+                                .help("The payload format (detected from the url by default)")
+                                .num_args(1)
+                                .value_parser(["discord", "slack", "generic"])
+                                .required(false),
+                        )
+                        .arg(
+                            Arg::new("template")
+                                .long("template")
+                                .short('t')
+                                .help("The message text, with {profile}, {event}, {message}, {time} and event fields like {player} or {code}")
+                                .num_args(1)
+                                .required(false),
+                        )
+                        .arg(
+                            Arg::new("retries")
+                                .long("retries")
+                                .help("How often to retry a failed delivery")
+                                .num_args(1)
+                                .default_value("3")
+                                .value_parser(clap::value_parser!(u32))
+                                .required(false),
+                        )
+                        .arg(
+                            Arg::new("backoff")
+                                .long("backoff")
+                                .help("The delay before the first retry, doubled for every further retry (seconds)")
+                                .num_args(1)
+                                .default_value("2")
+                                .value_parser(clap::value_parser!(u64))
+                                .required(false),
+                        )
+                        .arg_required_else_help(true),
+                )
+                .subcommand(
+                    Command::new("list")
+                        .about("Lists all webhooks")
+                        .arg_required_else_help(false),
+                )
+                .subcommand(
+                    Command::new("remove")
+                        .about("Removes a webhook")
+                        .arg(
+                            Arg::new("number")
+                                .help("The number of the webhook from `mcvcli webhook list`")
+                                .num_args(1)
+                                .value_parser(clap::value_parser!(usize))
+                                .required(false),
+                        )
+                        .arg_required_else_help(false),
+                )
+                .subcommand(
+                    Command::new("test")
+                        .about("Sends a test message to a webhook")
+                        .arg(
+                            Arg::new("number")
+                                .help("The number of the webhook from `mcvcli webhook list`")
+                                .num_args(1)
+                                .value_parser(clap::value_parser!(usize))
+                                .required(false),
+                        )
+                        .arg_required_else_help(false),
+                )
+                .arg_required_else_help(true)
+                .subcommand_required(true),
+        )
+        .subcommand(
+            Command::new("remote")
+                .about("Manages saved remote servers for --remote")
+                .subcommand(
+                    Command::new("add")
+                        .about("Saves a remote server")
+                        .arg(
+                            Arg::new("name")
+                                .help("The name to use with --remote")
+                                .num_args(1)
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::new("address")
+                                .help("The remote control address of the server (e.g., `example.com:25580`)")
+                                .num_args(1)
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::new("token")
+                                .long("token")
+                                .help("The remote token of the server (see `mcvcli config --remote-address`)")
+                                .num_args(1)
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::new("tls_cert")
+                                .long("tls-cert")
+                                .help("The client certificate (PEM) for a server with remote TLS")
+                                .num_args(1)
+                                .requires_all(["tls_key", "tls_ca"])
+                                .required(false),
+                        )
+                        .arg(
+                            Arg::new("tls_key")
+                                .long("tls-key")
+                                .help("The private key (PEM) of the client certificate")
+                                .num_args(1)
+                                .requires_all(["tls_cert", "tls_ca"])
+                                .required(false),
+                        )
+                        .arg(
+                            Arg::new("tls_ca")
+                                .long("tls-ca")
+                                .help("The CA certificate (PEM) the server certificate must be signed by")
+                                .num_args(1)
+                                .requires_all(["tls_cert", "tls_key"])
+                                .required(false),
+                        )
+                        .arg_required_else_help(true),
+                )
+                .subcommand(
+                    Command::new("list")
+                        .about("Lists all saved remote servers")
+                        .arg_required_else_help(false),
+                )
+                .subcommand(
+                    Command::new("remove")
+                        .about("Removes a saved remote server")
+                        .arg(
+                            Arg::new("name")
+                                .help("The name of the remote to remove")
+                                .num_args(1)
+                                .required(true),
+                        )
+                        .arg_required_else_help(true),
+                )
+                .arg_required_else_help(true)
+                .subcommand_required(true),
+        )
         .subcommand(
             Command::new("mods")
                 .about("Manages mods")
@@ -469,6 +1230,14 @@ fn cli() -> Command {
         .subcommand(
             Command::new("daemon")
                 .about("Internal detached server supervisor")
+                .arg(
+                    Arg::new("adopt")
+                        .long("adopt")
+                        .help("The pid of an orphaned server to take over instead of starting one")
+                        .num_args(1)
+                        .value_parser(clap::value_parser!(u32))
+                        .required(false),
+                )
                 .hide(true),
         )
 }
@@ -484,11 +1253,20 @@ async fn main() {
         Some(("install", sub_matches)) => commands::install::install(sub_matches).await,
         Some(("start", sub_matches)) => commands::start::start(sub_matches).await,
         Some(("stop", sub_matches)) => commands::stop::stop(sub_matches).await,
-        Some(("daemon", _)) => commands::daemon::run().await,
+        Some(("daemon", sub_matches)) => {
+            commands::daemon::run(sub_matches.get_one::<u32>("adopt").copied()).await
+        }
+        Some(("exec", sub_matches)) => commands::exec::exec(sub_matches).await,
         Some(("attach", sub_matches)) => commands::attach::attach(sub_matches).await,
         Some(("status", sub_matches)) => commands::status::status(sub_matches).await,
+        Some(("logs", sub_matches)) => commands::logs::logs(sub_matches).await,
+        Some(("restart", sub_matches)) => commands::restart::restart(sub_matches).await,
+        Some(("ps", sub_matches)) => commands::ps::ps(sub_matches).await,
         Some(("lookup", sub_matches)) => commands::lookup::lookup(sub_matches).await,
         Some(("query", sub_matches)) => commands::query::query(sub_matches).await,
+        Some(("rcon", sub_matches)) => commands::rcon::rcon(sub_matches).await,
+        Some(("recover", sub_matches)) => commands::recover::recover(sub_matches).await,
+        Some(("serve", sub_matches)) => commands::serve::serve(sub_matches).await,
         Some(("version", sub_matches)) => commands::version::version(sub_matches).await,
         Some(("update", sub_matches)) => commands::update::update(sub_matches).await,
         Some(("profile", sub_matches)) => match sub_matches.subcommand() {
@@ -507,6 +1285,40 @@ async fn main() {
             Some(("list", sub_matches)) => commands::backups::list::list(sub_matches).await,
             _ => unreachable!(),
         },
+        Some(("crash", sub_matches)) => match sub_matches.subcommand() {
+            Some(("list", sub_matches)) => commands::crash::list::list(sub_matches).await,
+            Some(("show", sub_matches)) => commands::crash::show::show(sub_matches).await,
+            _ => unreachable!(),
+        },
+        Some(("schedule", sub_matches)) => match sub_matches.subcommand() {
+            Some(("add", sub_matches)) => commands::schedule::add::add(sub_matches).await,
+            Some(("list", sub_matches)) => commands::schedule::list::list(sub_matches).await,
+            Some(("remove", sub_matches)) => commands::schedule::remove::remove(sub_matches).await,
+            _ => unreachable!(),
+        },
+        Some(("service", sub_matches)) => match sub_matches.subcommand() {
+            Some(("install", sub_matches)) => {
+                commands::service::install::install(sub_matches).await
+            }
+            Some(("uninstall", sub_matches)) => {
+                commands::service::uninstall::uninstall(sub_matches).await
+            }
+            Some(("status", sub_matches)) => commands::service::status::status(sub_matches).await,
+            _ => unreachable!(),
+        },
+        Some(("webhook", sub_matches)) => match sub_matches.subcommand() {
+            Some(("add", sub_matches)) => commands::webhook::add::add(sub_matches).await,
+            Some(("list", sub_matches)) => commands::webhook::list::list(sub_matches).await,
+            Some(("remove", sub_matches)) => commands::webhook::remove::remove(sub_matches).await,
+            Some(("test", sub_matches)) => commands::webhook::test::test(sub_matches).await,
+            _ => unreachable!(),
+        },
+        Some(("remote", sub_matches)) => match sub_matches.subcommand() {
+            Some(("add", sub_matches)) => commands::remote::add::add(sub_matches).await,
+            Some(("list", sub_matches)) => commands::remote::list::list(sub_matches).await,
+            Some(("remove", sub_matches)) => commands::remote::remove::remove(sub_matches).await,
+            _ => unreachable!(),
+        },
         Some(("mods", sub_matches)) => match sub_matches.subcommand() {
             Some(("list", sub_matches)) => commands::mods::list::list(sub_matches).await,
             Some(("delete", sub_matches)) => commands::mods::delete::delete(sub_matches).await,
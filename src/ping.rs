@@ -0,0 +1,19 @@
+use std::time::Duration;
+
+/// Server List Ping with socket timeouts, so a frozen server can't block the caller forever.
+pub fn ping(host: &str, port: u16, timeout: Duration) -> Result<msp::Server, msp::MspErr> {
+    let mut server = msp::Conf::create_with_port(host, port);
+    server.socket_conf.read_time_out = Some(timeout);
+    server.socket_conf.write_timeout = Some(timeout);
+
+    server.get_server_status()
+}
+
+/// Pings the server in this directory on the `server-port` from `server.properties`.
+pub async fn ping_local(timeout: Duration) -> bool {
+    let port = crate::properties::server_port();
+
+    tokio::task::spawn_blocking(move || ping("127.0.0.1", port, timeout).is_ok())
+        .await
+        .unwrap_or(false)
+}
@@ -22,3 +22,43 @@ pub fn list() -> Vec<String> {
 
     profiles
 }
+
+/// Moves the server directory into the profile `current` and the files of profile `name` into the
+/// server directory.
+pub fn switch(current: &str, name: &str) -> Result<(), anyhow::Error> {
+    let new_directory = format!(".mcvcli.profiles/{current}");
+    let old_directory = format!(".mcvcli.profiles/{name}");
+
+    if !Path::new(&new_directory).exists() {
+        std::fs::create_dir_all(&new_directory)?;
+    }
+
+    for entry in std::fs::read_dir(".")?.flatten() {
+        let path = entry.path();
+
+        let Some(file_name) = path.file_name() else {
+            continue;
+        };
+
+        if file_name == ".mcvcli.profiles" {
+            continue;
+        }
+
+        std::fs::rename(
+            &path,
+            format!("{}/{}", new_directory, file_name.to_string_lossy()),
+        )?;
+    }
+
+    for entry in std::fs::read_dir(&old_directory)?.flatten() {
+        let path = entry.path();
+
+        let Some(file_name) = path.file_name() else {
+            continue;
+        };
+
+        std::fs::rename(&path, format!("./{}", file_name.to_string_lossy()))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use std::{collections::HashMap, path::Path};
+
+/// Reads a java `.properties` file (like `server.properties`) into a map. Missing files are empty.
+pub fn read(path: impl AsRef<Path>) -> HashMap<String, String> {
+    let content = std::fs::read_to_string(path).unwrap_or_default();
+    let mut properties = HashMap::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with('!') {
+            continue;
+        }
+
+        if let Some((key, value)) = line.split_once('=') {
+            properties.insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+
+    properties
+}
+
+pub fn server_port() -> u16 {
+    read("server.properties")
+        .get("server-port")
+        .and_then(|port| port.parse().ok())
+        .unwrap_or(25565)
+}
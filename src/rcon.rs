@@ -0,0 +1,147 @@
+use std::time::Duration;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+const TYPE_RESPONSE: i32 = 0;
+const TYPE_COMMAND: i32 = 2;
+const TYPE_AUTH: i32 = 3;
+const TYPE_AUTH_RESPONSE: i32 = 2;
+
+/// The largest packet the server sends is 4096 bytes of body plus the header.
+const MAX_PACKET: usize = 4096 + 14;
+
+/// The `rcon.port`, `server-ip` and `rcon.password` from `server.properties`, with the defaults
+/// the server itself uses.
+pub fn local() -> (String, Option<String>) {
+    let properties = crate::properties::read("server.properties");
+
+    let host = properties
+        .get("server-ip")
+        .filter(|ip| !ip.is_empty())
+        .map_or("127.0.0.1", |ip| ip.as_str());
+    let port = properties
+        .get("rcon.port")
+        .and_then(|port| port.parse::<u16>().ok())
+        .unwrap_or(25575);
+    let password = properties
+        .get("rcon.password")
+        .filter(|password| !password.is_empty())
+        .cloned();
+
+    (format!("{host}:{port}"), password)
+}
+
+pub fn enabled() -> bool {
+    crate::properties::read("server.properties")
+        .get("enable-rcon")
+        .is_some_and(|enabled| enabled == "true")
+}
+
+/// A client for the Source RCON protocol, as implemented by the Minecraft server.
+pub struct Rcon {
+    stream: TcpStream,
+    timeout: Duration,
+    next_id: i32,
+}
+
+impl Rcon {
+    pub async fn connect(
+        address: &str,
+        password: &str,
+        timeout: Duration,
+    ) -> Result<Self, anyhow::Error> {
+        let stream = tokio::time::timeout(timeout, TcpStream::connect(address))
+            .await
+            .map_err(|_| anyhow::anyhow!("timed out connecting to {address}"))??;
+
+        let mut rcon = Self {
+            stream,
+            timeout,
+            next_id: 1,
+        };
+
+        let id = rcon.id();
+        rcon.send(id, TYPE_AUTH, password).await?;
+
+        loop {
+            let (response_id, kind, _) = rcon.receive().await?;
+            if kind != TYPE_AUTH_RESPONSE {
+                continue;
+            }
+
+            if response_id == -1 {
+                return Err(anyhow::anyhow!("wrong rcon password"));
+            }
+
+            return Ok(rcon);
+        }
+    }
+
+    fn id(&mut self) -> i32 {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1).max(1);
+
+        id
+    }
+
+    async fn send(&mut self, id: i32, kind: i32, body: &str) -> Result<(), anyhow::Error> {
+        let mut packet = Vec::with_capacity(body.len() + 14);
+        packet.extend_from_slice(&(body.len() as i32 + 10).to_le_bytes());
+        packet.extend_from_slice(&id.to_le_bytes());
+        packet.extend_from_slice(&kind.to_le_bytes());
+        packet.extend_from_slice(body.as_bytes());
+        packet.extend_from_slice(&[0, 0]);
+
+        self.stream.write_all(&packet).await?;
+
+        Ok(())
+    }
+
+    async fn receive(&mut self) -> Result<(i32, i32, Vec<u8>), anyhow::Error> {
+        tokio::time::timeout(self.timeout, async {
+            let length = self.stream.read_i32_le().await? as usize;
+            if !(10..=MAX_PACKET).contains(&length) {
+                return Err(anyhow::anyhow!("invalid rcon packet length {length}"));
+            }
+
+            let mut packet = vec![0; length];
+            self.stream.read_exact(&mut packet).await?;
+
+            let id = i32::from_le_bytes(packet[0..4].try_into()?);
+            let kind = i32::from_le_bytes(packet[4..8].try_into()?);
+            packet.truncate(length - 2);
+            packet.drain(..8);
+
+            Ok((id, kind, packet))
+        })
+        .await
+        .map_err(|_| anyhow::anyhow!("timed out waiting for the server"))?
+    }
+
+    /// Runs a command and returns its output. Long output is split over several packets, so an
+    /// invalid request is sent right after the command; the server answers requests in order, so
+    /// everything before its reply belongs to the command.
+    pub async fn command(&mut self, command: &str) -> Result<String, anyhow::Error> {
+        let id = self.id();
+        let sentinel = self.id();
+
+        self.send(id, TYPE_COMMAND, command).await?;
+        self.send(sentinel, TYPE_RESPONSE, "").await?;
+
+        let mut output = Vec::new();
+        loop {
+            let (response_id, _, body) = self.receive().await?;
+
+            if response_id == sentinel {
+                break;
+            }
+            if response_id == id {
+                output.extend_from_slice(&body);
+            }
+        }
+
+        Ok(String::from_utf8_lossy(&output).into_owned())
+    }
+}
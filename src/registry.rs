@@ -0,0 +1,74 @@
+use crate::detached;
+
+use dirs::home_dir;
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, sync::LazyLock};
+
+/// A detached server, as registered by its daemon in `~/.mcvcli/servers` so running servers can be
+/// found from anywhere on the machine.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Entry {
+    pub identifier: String,
+    pub directory: String,
+    pub profile: String,
+    pub daemon_pid: u32,
+    pub java_pid: u32,
+    pub port: u16,
+}
+
+static LOCATION: LazyLock<PathBuf> = LazyLock::new(|| {
+    home_dir()
+        .unwrap_or_default()
+        .join(".mcvcli")
+        .join("servers")
+});
+
+fn path(identifier: &str) -> PathBuf {
+    LOCATION.join(format!("{identifier}.json"))
+}
+
+pub fn register(entry: &Entry) -> Result<(), anyhow::Error> {
+    std::fs::create_dir_all(LOCATION.as_path())?;
+    let file = std::fs::File::create(path(&entry.identifier))?;
+    serde_json::to_writer_pretty(file, entry)?;
+
+    Ok(())
+}
+
+pub fn unregister(identifier: &str) {
+    let _ = std::fs::remove_file(path(identifier));
+}
+
+/// Lists the registered servers, removing the entries of daemons that are gone.
+pub fn list() -> Vec<Entry> {
+    let mut entries = Vec::new();
+
+    let Ok(files) = std::fs::read_dir(LOCATION.as_path()) else {
+        return entries;
+    };
+
+    let sys = sysinfo::System::new_all();
+
+    for file in files.flatten() {
+        let path = file.path();
+        let Some(entry) = std::fs::File::open(&path)
+            .ok()
+            .and_then(|file| serde_json::from_reader::<_, Entry>(file).ok())
+        else {
+            continue;
+        };
+
+        if !detached::process_alive(&sys, entry.daemon_pid, "mcvcli")
+            && !detached::process_alive(&sys, entry.java_pid, "java")
+        {
+            let _ = std::fs::remove_file(&path);
+            continue;
+        }
+
+        entries.push(entry);
+    }
+
+    entries.sort_by(|a, b| a.directory.cmp(&b.directory));
+
+    entries
+}
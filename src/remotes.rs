@@ -0,0 +1,69 @@
+use crate::detached::{self, Remote};
+
+use clap::ArgMatches;
+use dirs::home_dir;
+use std::{collections::BTreeMap, path::PathBuf, sync::LazyLock};
+
+/// Saved remotes (`mcvcli remote add`), so `--remote` can take a name instead of an address and
+/// token.
+static LOCATION: LazyLock<PathBuf> = LazyLock::new(|| {
+    home_dir()
+        .unwrap_or_default()
+        .join(".mcvcli")
+        .join("remotes.json")
+});
+
+pub fn read() -> BTreeMap<String, Remote> {
+    std::fs::File::open(LOCATION.as_path())
+        .ok()
+        .and_then(|file| serde_json::from_reader(file).ok())
+        .unwrap_or_default()
+}
+
+pub fn write(remotes: &BTreeMap<String, Remote>) -> Result<(), anyhow::Error> {
+    if let Some(parent) = LOCATION.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    // The file holds tokens, so it should only be readable by its owner.
+    let file = detached::create_private(LOCATION.as_path())?;
+    serde_json::to_writer_pretty(file, remotes)?;
+
+    Ok(())
+}
+
+/// The remote selected with `--remote` (a saved name or `host:port`) and `--token` (or the
+/// `MCVCLI_REMOTE_TOKEN` environment variable), if any.
+pub fn resolve(matches: &ArgMatches) -> Result<Option<Remote>, anyhow::Error> {
+    let Some(remote) = matches.get_one::<String>("remote") else {
+        return Ok(None);
+    };
+    let token = matches
+        .get_one::<String>("token")
+        .cloned()
+        .or_else(|| std::env::var("MCVCLI_REMOTE_TOKEN").ok());
+
+    if let Some(mut saved) = read().remove(remote) {
+        if let Some(token) = token {
+            saved.token = token;
+        }
+
+        return Ok(Some(saved));
+    }
+
+    if !remote.contains(':') {
+        return Err(anyhow::anyhow!(
+            "{remote} is not a saved remote or a host:port address"
+        ));
+    }
+
+    let token = token.ok_or_else(|| {
+        anyhow::anyhow!("no token for {remote}, use --token or MCVCLI_REMOTE_TOKEN")
+    })?;
+
+    Ok(Some(Remote {
+        address: remote.clone(),
+        token,
+        tls: None,
+    }))
+}
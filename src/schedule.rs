@@ -0,0 +1,183 @@
+use crate::{backups::BackupFormat, detached};
+
+use chrono::{DateTime, Datelike, Duration, Local, Timelike};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Action {
+    Command {
+        command: String,
+    },
+    Restart {
+        timeout: u64,
+        #[serde(default)]
+        countdown: u64,
+    },
+    Backup {
+        format: BackupFormat,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Job {
+    pub id: String,
+    pub expression: String,
+    pub action: Action,
+}
+
+impl Action {
+    pub fn describe(&self) -> String {
+        match self {
+            Action::Command { command } => format!("command `{command}`"),
+            Action::Restart { timeout, countdown } if *countdown > 0 => {
+                format!("restart in {countdown}s ({timeout}s timeout)")
+            }
+            Action::Restart { timeout, .. } => format!("restart ({timeout}s timeout)"),
+            Action::Backup { format } => format!("backup ({})", crate::backups::extension(format)),
+        }
+    }
+}
+
+/// A parsed 5-field cron expression (`minute hour day-of-month month day-of-week`), stored as
+/// bitmasks of the allowed values for each field.
+#[derive(Debug)]
+pub struct Cron {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, anyhow::Error> {
+    let mut mask = 0u64;
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>()?),
+            None => (part, 1),
+        };
+
+        if step == 0 {
+            return Err(anyhow::anyhow!("invalid step in `{part}`"));
+        }
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (start.parse::<u32>()?, end.parse::<u32>()?)
+        } else {
+            let value = range.parse::<u32>()?;
+            (value, if part.contains('/') { max } else { value })
+        };
+
+        if start < min || end > max || start > end {
+            return Err(anyhow::anyhow!("`{part}` is out of range ({min}-{max})"));
+        }
+
+        for value in (start..=end).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+
+    Ok(mask)
+}
+
+impl Cron {
+    pub fn parse(expression: &str) -> Result<Self, anyhow::Error> {
+        let expression = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            expression => expression,
+        };
+
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minutes, hours, days, months, weekdays] = fields[..] else {
+            return Err(anyhow::anyhow!(
+                "expected 5 fields (minute hour day month weekday), got {}",
+                fields.len()
+            ));
+        };
+
+        let mut weekdays_mask = parse_field(weekdays, 0, 7)?;
+        // Both 0 and 7 mean sunday.
+        if weekdays_mask & (1 << 7) != 0 {
+            weekdays_mask |= 1;
+        }
+
+        Ok(Cron {
+            minutes: parse_field(minutes, 0, 59)?,
+            hours: parse_field(hours, 0, 23)?,
+            days: parse_field(days, 1, 31)?,
+            months: parse_field(months, 1, 12)?,
+            weekdays: weekdays_mask,
+            // Like in cron, a field that starts with `*` (including steps like `*/2`) doesn't
+            // restrict the other day field.
+            days_restricted: !days.starts_with('*'),
+            weekdays_restricted: !weekdays.starts_with('*'),
+        })
+    }
+
+    pub fn matches(&self, time: &DateTime<Local>) -> bool {
+        let day = self.days & (1 << time.day()) != 0;
+        let weekday = self.weekdays & (1 << time.weekday().num_days_from_sunday()) != 0;
+
+        // Like cron, a restricted day-of-month and day-of-week match if either one does.
+        let day_matches = match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => day || weekday,
+            (true, false) => day,
+            (false, true) => weekday,
+            (false, false) => true,
+        };
+
+        self.minutes & (1 << time.minute()) != 0
+            && self.hours & (1 << time.hour()) != 0
+            && self.months & (1 << time.month()) != 0
+            && day_matches
+    }
+
+    pub fn next_after(&self, time: &DateTime<Local>) -> Option<DateTime<Local>> {
+        let mut next = time.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+
+        for _ in 0..(366 * 24 * 60) {
+            if self.matches(&next) {
+                return Some(next);
+            }
+
+            next += Duration::minutes(1);
+        }
+
+        None
+    }
+}
+
+/// The scheduled jobs, none if there is no schedule file yet.
+pub fn read() -> Result<Vec<Job>, anyhow::Error> {
+    let file = match std::fs::File::open(detached::schedule_path()) {
+        Ok(file) => file,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err.into()),
+    };
+
+    serde_json::from_reader(std::io::BufReader::new(file)).map_err(|err| {
+        anyhow::anyhow!(
+            "{} is not valid: {err}",
+            detached::schedule_path().display()
+        )
+    })
+}
+
+pub fn write(jobs: &[Job]) -> Result<(), anyhow::Error> {
+    std::fs::create_dir_all(detached::dir())?;
+    let file = std::fs::File::create(detached::schedule_path())?;
+    serde_json::to_writer_pretty(file, jobs)?;
+
+    Ok(())
+}
@@ -0,0 +1,261 @@
+use crate::detached;
+
+use std::path::{Path, PathBuf};
+
+/// The init systems `mcvcli service` can write a service for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Init {
+    Systemd,
+    OpenRc,
+}
+
+impl Init {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "systemd" => Some(Init::Systemd),
+            "openrc" => Some(Init::OpenRc),
+            _ => None,
+        }
+    }
+
+    /// The init system this machine was booted with, if it is one we support.
+    pub fn detect() -> Option<Self> {
+        if Path::new("/run/systemd/system").exists() {
+            Some(Init::Systemd)
+        } else if Path::new("/sbin/openrc-run").exists() {
+            Some(Init::OpenRc)
+        } else {
+            None
+        }
+    }
+}
+
+/// A service for the server in `directory`.
+#[derive(Debug, Clone)]
+pub struct Service {
+    pub init: Init,
+    /// A systemd system unit instead of a user unit. OpenRC services are always system services.
+    pub system: bool,
+    pub name: String,
+    pub directory: PathBuf,
+    /// Where the unit file goes. Only the default locations are known to the init system, so a
+    /// service written anywhere else is never enabled or queried.
+    pub unit_dir: PathBuf,
+    pub custom_dir: bool,
+}
+
+/// What goes into the generated unit besides the server directory.
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub executable: PathBuf,
+    pub user: Option<String>,
+    /// systemd `Restart=` for the daemon, which exits with the server's exit code.
+    pub restart: String,
+    pub environment: Vec<(String, String)>,
+}
+
+/// Variables passed through to the service when they are set here, so it finds the same java
+/// and APIs as the shell it was installed from.
+pub const PASSTHROUGH_ENV: [&str; 4] = ["PATH", "JAVA_HOME", "MCJARS_URL", "MODRINTH_API_URL"];
+
+/// The default service name for a server directory, e.g. `mcvcli-survival`.
+pub fn default_name(directory: &Path) -> String {
+    let base = directory
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    format!("mcvcli-{}", sanitize(&base))
+        .trim_end_matches('-')
+        .to_string()
+}
+
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' | '.' => c,
+            _ => '-',
+        })
+        .collect()
+}
+
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && !name.starts_with('.') && sanitize(name) == name
+}
+
+/// The directory the init system loads services of this kind from.
+pub fn default_unit_dir(init: Init, system: bool) -> Option<PathBuf> {
+    match (init, system) {
+        (Init::Systemd, true) => Some(PathBuf::from("/etc/systemd/system")),
+        (Init::Systemd, false) => Some(dirs::config_dir()?.join("systemd").join("user")),
+        (Init::OpenRc, _) => Some(PathBuf::from("/etc/init.d")),
+    }
+}
+
+impl Service {
+    pub fn file_name(&self) -> String {
+        match self.init {
+            Init::Systemd => format!("{}.service", self.name),
+            Init::OpenRc => self.name.clone(),
+        }
+    }
+
+    pub fn path(&self) -> PathBuf {
+        self.unit_dir.join(self.file_name())
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match (self.init, self.system) {
+            (Init::Systemd, true) => "systemd system unit",
+            (Init::Systemd, false) => "systemd user unit",
+            (Init::OpenRc, _) => "openrc service",
+        }
+    }
+
+    pub fn render(&self, options: &Options) -> String {
+        match self.init {
+            Init::Systemd => self.render_systemd(options),
+            Init::OpenRc => self.render_openrc(options),
+        }
+    }
+
+    /// The daemon model maps onto `Type=forking`: `mcvcli start --detached` returns once the
+    /// daemon is up, and the daemon writes its pid file. The daemon moves the server into its own
+    /// cgroup for resource limits, which needs `Delegate=yes`.
+    fn render_systemd(&self, options: &Options) -> String {
+        let directory = self.directory.to_string_lossy();
+        let executable = exec_quote(&options.executable.to_string_lossy());
+        let pid_file = self.directory.join(detached::pid_path());
+
+        let mut unit = vec![
+            format!("# Generated by mcvcli for {directory}, manage it with `mcvcli service`."),
+            "[Unit]".to_string(),
+            format!(
+                "Description=Minecraft server in {} (mcvcli)",
+                escape(&directory)
+            ),
+            "After=network-online.target".to_string(),
+            "Wants=network-online.target".to_string(),
+            String::new(),
+            "[Service]".to_string(),
+            "Type=forking".to_string(),
+            format!("PIDFile={}", escape(&pid_file.to_string_lossy())),
+            format!("WorkingDirectory={}", escape(&directory)),
+        ];
+        if self.system
+            && let Some(user) = &options.user
+        {
+            unit.push(format!("User={user}"));
+        }
+        for (key, value) in &options.environment {
+            unit.push(format!("Environment={}", quote(&format!("{key}={value}"))));
+        }
+        unit.extend([
+            format!("ExecStart={executable} start --detached"),
+            format!("ExecStop={executable} stop"),
+            format!("ExecReload={executable} restart"),
+            format!("Restart={}", options.restart),
+            "RestartSec=10".to_string(),
+            "TimeoutStartSec=120".to_string(),
+            "TimeoutStopSec=120".to_string(),
+            "Delegate=yes".to_string(),
+            String::new(),
+            "[Install]".to_string(),
+            format!(
+                "WantedBy={}",
+                match self.system {
+                    true => "multi-user.target",
+                    false => "default.target",
+                }
+            ),
+        ]);
+
+        unit.join("\n") + "\n"
+    }
+
+    fn render_openrc(&self, options: &Options) -> String {
+        let directory = self.directory.to_string_lossy();
+
+        let mut script = vec![
+            "#!/sbin/openrc-run".to_string(),
+            format!("# Generated by mcvcli for {directory}, manage it with `mcvcli service`."),
+            String::new(),
+            format!(
+                "description={}",
+                shell_quote(&format!("Minecraft server in {directory}"))
+            ),
+            format!("directory={}", shell_quote(&directory)),
+            format!(
+                "mcvcli={}",
+                shell_quote(&options.executable.to_string_lossy())
+            ),
+            format!(
+                "user={}",
+                shell_quote(options.user.as_deref().unwrap_or("root"))
+            ),
+        ];
+        for (key, value) in &options.environment {
+            script.push(format!("export {key}={}", shell_quote(value)));
+        }
+        script.push(String::new());
+        script.push(
+            r#"extra_started_commands="reload"
+
+depend() {
+	need net
+}
+
+run_mcvcli() {
+	cd "${directory}" || return 1
+	if [ "$(id -un)" = "${user}" ]; then
+		"${mcvcli}" "$@"
+	else
+		su -s /bin/sh "${user}" -c '"$0" "$@"' "${mcvcli}" "$@"
+	fi
+}
+
+start() {
+	ebegin "Starting ${RC_SVCNAME}"
+	run_mcvcli start --detached
+	eend $?
+}
+
+stop() {
+	ebegin "Stopping ${RC_SVCNAME}"
+	run_mcvcli stop
+	eend $?
+}
+
+reload() {
+	ebegin "Restarting the server of ${RC_SVCNAME}"
+	run_mcvcli restart
+	eend $?
+}"#
+            .to_string(),
+        );
+
+        script.join("\n") + "\n"
+    }
+}
+
+/// Escapes systemd specifiers, which are expanded in almost every setting.
+fn escape(value: &str) -> String {
+    value.replace('%', "%%")
+}
+
+/// A double-quoted systemd value.
+fn quote(value: &str) -> String {
+    format!(
+        "\"{}\"",
+        escape(value).replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
+
+/// A quoted command for `Exec*=`, which also expands `$` variables.
+fn exec_quote(value: &str) -> String {
+    quote(value).replace('$', "$$")
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio_rustls::{
+    TlsAcceptor, TlsConnector,
+    rustls::{
+        ClientConfig, RootCertStore, ServerConfig,
+        pki_types::{CertificateDer, PrivateKeyDer, ServerName, pem::PemObject},
+        server::WebPkiClientVerifier,
+    },
+};
+
+/// Mutual TLS for remote control: each side presents `certificate` (with its `key`) and only
+/// trusts a peer whose certificate was signed by `ca`. All three are PEM files.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tls {
+    pub certificate: String,
+    pub key: String,
+    pub ca: String,
+}
+
+impl Tls {
+    fn certificates(&self) -> Result<Vec<CertificateDer<'static>>, anyhow::Error> {
+        CertificateDer::pem_file_iter(&self.certificate)
+            .and_then(|certificates| certificates.collect::<Result<Vec<_>, _>>())
+            .map_err(|err| anyhow::anyhow!("failed to read {}: {err}", self.certificate))
+    }
+
+    fn private_key(&self) -> Result<PrivateKeyDer<'static>, anyhow::Error> {
+        PrivateKeyDer::from_pem_file(&self.key)
+            .map_err(|err| anyhow::anyhow!("failed to read {}: {err}", self.key))
+    }
+
+    fn roots(&self) -> Result<RootCertStore, anyhow::Error> {
+        let mut roots = RootCertStore::empty();
+        for certificate in CertificateDer::pem_file_iter(&self.ca)
+            .map_err(|err| anyhow::anyhow!("failed to read {}: {err}", self.ca))?
+        {
+            let certificate =
+                certificate.map_err(|err| anyhow::anyhow!("failed to read {}: {err}", self.ca))?;
+            roots.add(certificate)?;
+        }
+
+        Ok(roots)
+    }
+
+    /// The daemon side, which requires a client certificate signed by `ca`.
+    pub fn acceptor(&self) -> Result<TlsAcceptor, anyhow::Error> {
+        let verifier = WebPkiClientVerifier::builder(Arc::new(self.roots()?)).build()?;
+        let config = ServerConfig::builder()
+            .with_client_cert_verifier(verifier)
+            .with_single_cert(self.certificates()?, self.private_key()?)?;
+
+        Ok(TlsAcceptor::from(Arc::new(config)))
+    }
+
+    /// The client side, which requires the daemon's certificate to be signed by `ca` and to be
+    /// issued for the host of `address`.
+    pub fn connector(
+        &self,
+        address: &str,
+    ) -> Result<(TlsConnector, ServerName<'static>), anyhow::Error> {
+        let host = address
+            .rsplit_once(':')
+            .map_or(address, |(host, _)| host)
+            .trim_start_matches('[')
+            .trim_end_matches(']');
+        let name = ServerName::try_from(host.to_string())
+            .map_err(|_| anyhow::anyhow!("{host} is not a valid host name"))?;
+
+        let config = ClientConfig::builder()
+            .with_root_certificates(self.roots()?)
+            .with_client_auth_cert(self.certificates()?, self.private_key()?)?;
+
+        Ok((TlsConnector::from(Arc::new(config)), name))
+    }
+}
@@ -0,0 +1,99 @@
+use crate::detached;
+
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, Write};
+
+/// How often the daemon samples the server process.
+pub const SAMPLE_INTERVAL_SECS: u64 = 30;
+/// The amount of samples kept on disk, a week at the default interval.
+pub const MAX_SAMPLES: usize = 7 * 24 * 60 * 60 / SAMPLE_INTERVAL_SECS as usize;
+
+/// A single resource usage sample of the server process. `cpu` is in percent of one core, disk
+/// I/O is in bytes since the previous sample.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sample {
+    pub time: i64,
+    pub cpu: f32,
+    pub memory: u64,
+    pub threads: usize,
+    pub disk_read: u64,
+    pub disk_written: u64,
+}
+
+pub struct Sampler {
+    system: sysinfo::System,
+}
+
+impl Sampler {
+    pub fn new() -> Self {
+        Sampler {
+            system: sysinfo::System::new(),
+        }
+    }
+
+    /// Samples a process. CPU and disk usage are measured since the previous call, so the first
+    /// sample of a process reads as idle.
+    pub fn sample(&mut self, pid: u32) -> Option<Sample> {
+        let pid = sysinfo::Pid::from(pid as usize);
+        self.system.refresh_processes_specifics(
+            sysinfo::ProcessesToUpdate::Some(&[pid]),
+            true,
+            sysinfo::ProcessRefreshKind::nothing()
+                .with_cpu()
+                .with_memory()
+                .with_disk_usage()
+                .with_tasks(),
+        );
+
+        let process = self.system.process(pid)?;
+        let disk = process.disk_usage();
+
+        Some(Sample {
+            time: chrono::Utc::now().timestamp(),
+            cpu: process.cpu_usage(),
+            memory: process.memory(),
+            // The main thread is not part of its own tasks.
+            threads: process.tasks().map_or(0, |tasks| tasks.len() + 1),
+            disk_read: disk.read_bytes,
+            disk_written: disk.written_bytes,
+        })
+    }
+}
+
+/// Samples taken at or after `since` (a unix timestamp), oldest first.
+pub fn read(since: i64) -> Vec<Sample> {
+    let Ok(file) = std::fs::File::open(detached::usage_path()) else {
+        return Vec::new();
+    };
+
+    std::io::BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<Sample>(&line).ok())
+        .filter(|sample| sample.time >= since)
+        .collect()
+}
+
+/// Appends a sample to the on-disk series, which is trimmed back to `MAX_SAMPLES` once it has
+/// grown past twice that. `count` tracks the amount of samples in the file.
+pub fn append(sample: &Sample, count: &mut usize) -> Result<(), anyhow::Error> {
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(detached::usage_path())?;
+    writeln!(file, "{}", serde_json::to_string(sample)?)?;
+    *count += 1;
+
+    if *count > MAX_SAMPLES * 2 {
+        let samples = read(i64::MIN);
+        let skip = samples.len().saturating_sub(MAX_SAMPLES);
+
+        let mut file = std::fs::File::create(detached::usage_path())?;
+        for sample in &samples[skip..] {
+            writeln!(file, "{}", serde_json::to_string(sample)?)?;
+        }
+        *count = samples.len() - skip;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,252 @@
+use crate::api;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::time::Duration;
+
+fn default_retries() -> u32 {
+    3
+}
+fn default_backoff_secs() -> u64 {
+    2
+}
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Event {
+    Start,
+    Ready,
+    Stop,
+    Crash,
+    Restart,
+    Join,
+    Leave,
+    Backup,
+    Restore,
+    Update,
+    /// Sent by `mcvcli webhook test`, regardless of the event filter.
+    Test,
+}
+
+impl Event {
+    pub const ALL: [&str; 10] = [
+        "start", "ready", "stop", "crash", "restart", "join", "leave", "backup", "restore",
+        "update",
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::Start => "start",
+            Event::Ready => "ready",
+            Event::Stop => "stop",
+            Event::Crash => "crash",
+            Event::Restart => "restart",
+            Event::Join => "join",
+            Event::Leave => "leave",
+            Event::Backup => "backup",
+            Event::Restore => "restore",
+            Event::Update => "update",
+            Event::Test => "test",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        serde_json::from_value(Value::String(name.to_string())).ok()
+    }
+}
+
+/// The shape of the request body.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Format {
+    /// `{"content": ...}` for Discord webhooks.
+    Discord,
+    /// `{"text": ...}` for Slack incoming webhooks.
+    Slack,
+    /// The event with all of its fields, for anything else.
+    #[default]
+    Generic,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Webhook {
+    pub url: String,
+    /// The events to send, all of them when empty.
+    #[serde(default)]
+    pub events: Vec<Event>,
+    #[serde(default)]
+    pub format: Format,
+    /// The message text, with `{profile}`, `{event}`, `{message}`, `{time}` and the fields of the
+    /// event (e.g. `{player}` or `{code}`) filled in. Defaults to `[{profile}] {message}`.
+    #[serde(default)]
+    pub template: Option<String>,
+    #[serde(default = "default_retries")]
+    pub retries: u32,
+    #[serde(default = "default_backoff_secs")]
+    pub backoff_secs: u64,
+}
+
+impl Webhook {
+    /// The format that fits the url, for known chat services.
+    pub fn detect_format(url: &str) -> Format {
+        match reqwest::Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+        {
+            Some(host)
+                if ["discord.com", "discordapp.com"]
+                    .iter()
+                    .any(|domain| host == *domain || host.ends_with(&format!(".{domain}"))) =>
+            {
+                Format::Discord
+            }
+            Some(host) if host == "hooks.slack.com" => Format::Slack,
+            _ => Format::Generic,
+        }
+    }
+
+    /// The host of the url, for messages. Chat webhook urls contain their secret in the path.
+    pub fn host(&self) -> String {
+        reqwest::Url::parse(&self.url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+            .unwrap_or_else(|| "invalid url".to_string())
+    }
+
+    pub fn wants(&self, event: Event) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+}
+
+/// Something that happened to a server, with a human readable message and the details that
+/// templates and the generic format can use.
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub event: Event,
+    pub message: String,
+    pub fields: Vec<(&'static str, String)>,
+}
+
+impl Notification {
+    pub fn new(event: Event, message: impl Into<String>) -> Self {
+        Notification {
+            event,
+            message: message.into(),
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn field(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.fields.push((name, value.to_string()));
+        self
+    }
+}
+
+fn render(webhook: &Webhook, profile: &str, notification: &Notification) -> Value {
+    let time = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+
+    let mut text = webhook
+        .template
+        .clone()
+        .unwrap_or_else(|| "[{profile}] {message}".to_string());
+    for (name, value) in [
+        ("profile", profile),
+        ("event", notification.event.name()),
+        ("message", &notification.message),
+        ("time", &time),
+    ]
+    .into_iter()
+    .chain(
+        notification
+            .fields
+            .iter()
+            .map(|(name, value)| (*name, value.as_str())),
+    ) {
+        text = text.replace(&format!("{{{name}}}"), value);
+    }
+
+    match webhook.format {
+        Format::Discord => json!({ "content": text }),
+        Format::Slack => json!({ "text": text }),
+        Format::Generic => json!({
+            "event": notification.event.name(),
+            "profile": profile,
+            "message": notification.message,
+            "text": text,
+            "time": time,
+            "fields": notification
+                .fields
+                .iter()
+                .map(|(name, value)| (name.to_string(), Value::String(value.clone())))
+                .collect::<serde_json::Map<String, Value>>(),
+        }),
+    }
+}
+
+/// Posts the notification to one webhook, retrying with exponential backoff on network errors,
+/// rate limits and server errors. Other client errors won't get better by retrying.
+pub async fn deliver(
+    webhook: &Webhook,
+    profile: &str,
+    notification: &Notification,
+) -> Result<(), anyhow::Error> {
+    let body = render(webhook, profile, notification);
+    let mut attempt = 0;
+
+    loop {
+        let error = match api::CLIENT
+            .post(&webhook.url)
+            .json(&body)
+            .timeout(TIMEOUT)
+            .send()
+            .await
+        {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response)
+                if response.status().is_client_error()
+                    && response.status() != reqwest::StatusCode::TOO_MANY_REQUESTS =>
+            {
+                return Err(anyhow::anyhow!("{}", response.status()));
+            }
+            Ok(response) => anyhow::anyhow!("{}", response.status()),
+            Err(err) => err.without_url().into(),
+        };
+
+        if attempt >= webhook.retries {
+            return Err(error);
+        }
+
+        tokio::time::sleep(Duration::from_secs(
+            webhook.backoff_secs.saturating_mul(1 << attempt.min(16)),
+        ))
+        .await;
+        attempt += 1;
+    }
+}
+
+/// Sends the notification to every webhook that wants it, returns the hosts of the ones that
+/// failed.
+pub async fn send(
+    webhooks: &[Webhook],
+    profile: &str,
+    notification: &Notification,
+) -> Vec<(String, anyhow::Error)> {
+    let deliveries = webhooks
+        .iter()
+        .filter(|webhook| webhook.wants(notification.event))
+        .map(|webhook| async move {
+            deliver(webhook, profile, notification)
+                .await
+                .err()
+                .map(|err| (webhook.host(), err))
+        });
+
+    futures::future::join_all(deliveries)
+        .await
+        .into_iter()
+        .flatten()
+        .collect()
+}